@@ -0,0 +1,3 @@
+ALTER TABLE contacts DROP CONSTRAINT contacts_phone_key;
+
+CREATE INDEX idx_contacts_phone ON contacts(phone);
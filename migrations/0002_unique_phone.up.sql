@@ -0,0 +1,3 @@
+DROP INDEX idx_contacts_phone;
+
+ALTER TABLE contacts ADD CONSTRAINT contacts_phone_key UNIQUE (phone);
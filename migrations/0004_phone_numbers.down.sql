@@ -0,0 +1 @@
+DROP TABLE phone_numbers;
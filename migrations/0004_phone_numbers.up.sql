@@ -0,0 +1,13 @@
+CREATE TABLE phone_numbers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES contacts(id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    number TEXT NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX idx_phone_numbers_contact_id ON phone_numbers(contact_id);
+CREATE INDEX idx_phone_numbers_number ON phone_numbers(number);
+
+INSERT INTO phone_numbers (contact_id, label, number)
+SELECT id, 'mobile', phone FROM contacts;
@@ -0,0 +1,8 @@
+-- Soft-deleted rows may share a phone with a live contact, and the column that
+-- marks them is going away, so they are purged.
+DELETE FROM contacts WHERE deleted_at IS NOT NULL;
+
+DROP INDEX contacts_phone_key;
+ALTER TABLE contacts ADD CONSTRAINT contacts_phone_key UNIQUE (phone);
+
+ALTER TABLE contacts DROP COLUMN deleted_at;
@@ -0,0 +1,6 @@
+ALTER TABLE contacts ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- Deleted contacts keep their phone number, so uniqueness only applies to
+-- live rows.
+ALTER TABLE contacts DROP CONSTRAINT contacts_phone_key;
+CREATE UNIQUE INDEX contacts_phone_key ON contacts(phone) WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+DROP INDEX idx_contacts_full_name_trgm;
+
+DROP EXTENSION IF EXISTS pg_trgm;
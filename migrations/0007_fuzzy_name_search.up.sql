@@ -0,0 +1,4 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX idx_contacts_full_name_trgm
+    ON contacts USING GIN ((first_name || ' ' || last_name) gin_trgm_ops);
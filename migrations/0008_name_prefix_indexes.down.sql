@@ -0,0 +1,2 @@
+DROP INDEX idx_contacts_last_name_lower;
+DROP INDEX idx_contacts_first_name_lower;
@@ -0,0 +1,3 @@
+-- `text_pattern_ops` lets `LIKE 'prefix%'` use the index under any collation.
+CREATE INDEX idx_contacts_first_name_lower ON contacts (lower(first_name) text_pattern_ops);
+CREATE INDEX idx_contacts_last_name_lower ON contacts (lower(last_name) text_pattern_ops);
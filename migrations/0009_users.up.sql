@@ -0,0 +1,7 @@
+CREATE TABLE users (
+    id UUID PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    -- Argon2 PHC string, e.g. `$argon2id$v=19$m=19456,t=2,p=1$...`.
+    password_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
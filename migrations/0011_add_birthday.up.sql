@@ -0,0 +1 @@
+ALTER TABLE contacts ADD COLUMN birthday DATE;
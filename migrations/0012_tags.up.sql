@@ -0,0 +1,12 @@
+CREATE TABLE tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE contact_tags (
+    contact_id UUID NOT NULL REFERENCES contacts(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (contact_id, tag_id)
+);
+
+CREATE INDEX idx_contact_tags_tag_id ON contact_tags(tag_id);
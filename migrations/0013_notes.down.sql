@@ -0,0 +1,2 @@
+ALTER TABLE contacts DROP COLUMN notes_tsv;
+ALTER TABLE contacts DROP COLUMN notes;
@@ -0,0 +1,7 @@
+ALTER TABLE contacts ADD COLUMN notes TEXT;
+
+ALTER TABLE contacts
+    ADD COLUMN notes_tsv TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('english', coalesce(notes, ''))) STORED;
+
+CREATE INDEX idx_contacts_notes_tsv ON contacts USING GIN (notes_tsv);
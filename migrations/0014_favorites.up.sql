@@ -0,0 +1 @@
+ALTER TABLE contacts ADD COLUMN is_favorite BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,3 @@
+ALTER TABLE contacts ADD COLUMN company TEXT;
+
+CREATE INDEX idx_contacts_company ON contacts(lower(company));
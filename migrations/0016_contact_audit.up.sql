@@ -0,0 +1,13 @@
+-- No foreign key to contacts: the history has to outlive contacts that are
+-- removed for good, e.g. the duplicates folded in by a merge.
+CREATE TABLE contact_audit (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    contact_id UUID NOT NULL,
+    action TEXT NOT NULL,
+    changed_by TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    old_data JSONB,
+    new_data JSONB
+);
+
+CREATE INDEX idx_contact_audit_contact_id ON contact_audit(contact_id, id);
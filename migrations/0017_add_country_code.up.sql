@@ -0,0 +1,3 @@
+-- ISO 3166-1 alpha-2, upper-case; the region national-format numbers are
+-- read in.
+ALTER TABLE contacts ADD COLUMN country_code CHAR(2);
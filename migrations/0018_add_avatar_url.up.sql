@@ -0,0 +1,2 @@
+-- Absolute http(s) URL of a profile picture hosted elsewhere.
+ALTER TABLE contacts ADD COLUMN avatar_url TEXT;
@@ -0,0 +1,6 @@
+ALTER TABLE contacts
+    DROP COLUMN street,
+    DROP COLUMN city,
+    DROP COLUMN state,
+    DROP COLUMN postal_code,
+    DROP COLUMN country;
@@ -0,0 +1,8 @@
+-- Mailing address, one nullable column per part; the API nests them under
+-- `address`.
+ALTER TABLE contacts
+    ADD COLUMN street TEXT,
+    ADD COLUMN city TEXT,
+    ADD COLUMN state TEXT,
+    ADD COLUMN postal_code TEXT,
+    ADD COLUMN country TEXT;
@@ -0,0 +1,5 @@
+-- When the user last got in touch with the contact; set by
+-- `POST /contacts/{id}/touch`, NULL if never.
+ALTER TABLE contacts ADD COLUMN last_contacted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_contacts_last_contacted_at ON contacts(last_contacted_at NULLS FIRST);
@@ -0,0 +1,8 @@
+-- Requests per credential and calendar month (UTC), e.g. `api_key:1a2b3c4d`
+-- or `user:alice`. Written by `usage::Usage::flush`.
+CREATE TABLE api_usage (
+    client TEXT NOT NULL,
+    period DATE NOT NULL,
+    requests BIGINT NOT NULL,
+    PRIMARY KEY (client, period)
+);
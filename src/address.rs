@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// Mailing address of a contact. Every part is optional.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct Address {
+    pub street: Option<String>,
+    pub city: Option<String>,
+    /// State, province, or region.
+    pub state: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+}
+
+/// The address columns of a `contacts` row, which are flat in SQL but nested
+/// in JSON.
+#[derive(FromRow)]
+pub struct AddressColumns {
+    street: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    postal_code: Option<String>,
+    country: Option<String>,
+}
+
+/// A contact without any address part has no address at all.
+impl From<AddressColumns> for Option<Address> {
+    fn from(columns: AddressColumns) -> Self {
+        let address = Address {
+            street: columns.street,
+            city: columns.city,
+            state: columns.state,
+            postal_code: columns.postal_code,
+            country: columns.country,
+        };
+        (!address.is_empty()).then_some(address)
+    }
+}
+
+impl Address {
+    /// Each part with its field name, in storage order.
+    pub fn parts(&self) -> [(&'static str, Option<&str>); 5] {
+        [
+            ("street", self.street.as_deref()),
+            ("city", self.city.as_deref()),
+            ("state", self.state.as_deref()),
+            ("postal_code", self.postal_code.as_deref()),
+            ("country", self.country.as_deref()),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts().iter().all(|(_, part)| part.is_none())
+    }
+
+    /// Trims every part, treating blank ones as unset.
+    pub fn normalized(&self) -> Address {
+        let part = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        Address {
+            street: part(&self.street),
+            city: part(&self.city),
+            state: part(&self.state),
+            postal_code: part(&self.postal_code),
+            country: part(&self.country),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::convert::Infallible;
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{
+    types::{Json, JsonValue},
+    FromRow, PgConnection,
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, Contact};
+
+/// What happened to a contact.
+#[derive(Clone, Copy)]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    /// Permanently removed from the trash.
+    Purge,
+}
+
+impl Action {
+    fn as_str(self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            Action::Restore => "restore",
+            Action::Purge => "purge",
+        }
+    }
+}
+
+/// Who is making a change: the username behind a validated JWT, or nobody in
+/// particular for API-key and unauthenticated requests.
+pub struct Actor(Option<String>);
+
+impl Actor {
+    /// Changes made by an admin subcommand such as `seed`.
+    pub fn cli() -> Self {
+        Actor(Some("cli".to_string()))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Actor
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Set by `require_auth` for token-authenticated requests.
+        Ok(Actor(
+            parts
+                .extensions
+                .get::<AuthUser>()
+                .map(|user| user.username.clone()),
+        ))
+    }
+}
+
+/// One change to a contact, with full snapshots of the contact (including
+/// phones and tags) before and after it.
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct AuditEntry {
+    id: i64,
+    contact_id: Uuid,
+    /// One of `create`, `update`, `delete`, `restore`, or `purge`.
+    action: String,
+    /// Username of the token holder, `cli` for admin subcommands, and `null`
+    /// for API-key requests.
+    changed_by: Option<String>,
+    changed_at: DateTime<Utc>,
+    /// `null` for `create`.
+    #[schema(value_type = Option<Object>)]
+    old_data: Option<JsonValue>,
+    /// `null` for `purge`, and when a merge removed the contact for good.
+    #[schema(value_type = Option<Object>)]
+    new_data: Option<JsonValue>,
+}
+
+/// Appends an entry to the audit log. Call it on the connection of the
+/// transaction making the change, so the entry commits or rolls back with it.
+pub async fn record(
+    conn: &mut PgConnection,
+    actor: &Actor,
+    contact_id: Uuid,
+    action: Action,
+    old: Option<&Contact>,
+    new: Option<&Contact>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO contact_audit (contact_id, action, changed_by, old_data, new_data)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(contact_id)
+    .bind(action.as_str())
+    .bind(actor.0.as_deref())
+    .bind(old.map(Json))
+    .bind(new.map(Json))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Every audit entry of a contact, oldest first.
+pub async fn history(
+    conn: &mut PgConnection,
+    contact_id: Uuid,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    sqlx::query_as::<_, AuditEntry>("SELECT * FROM contact_audit WHERE contact_id = $1 ORDER BY id")
+        .bind(contact_id)
+        .fetch_all(conn)
+        .await
+}
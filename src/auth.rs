@@ -0,0 +1,244 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use jsonwebtoken::{errors::ErrorKind, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    usage::{self, Usage},
+};
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Credentials accepted by [`require_auth`]. Each scheme is optional; when
+/// neither is configured the middleware isn't installed at all.
+#[derive(Clone)]
+pub struct AuthConfig {
+    api_key: Option<Arc<str>>,
+    /// How requests with `api_key` are counted in `usage`.
+    api_key_client: Option<Arc<str>>,
+    jwt: Option<JwtKeys>,
+    /// When false, safe methods (`GET`, `HEAD`, `OPTIONS`) need no credentials.
+    protect_reads: bool,
+    /// Requests counted per credential that authenticated them.
+    usage: Usage,
+}
+
+impl AuthConfig {
+    pub fn new(api_key: Option<String>, jwt: Option<JwtKeys>, protect_reads: bool) -> Self {
+        Self {
+            api_key_client: api_key
+                .as_deref()
+                .map(|key| usage::api_key_client(key).into()),
+            api_key: api_key.map(Into::into),
+            jwt,
+            protect_reads,
+            usage: Usage::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.api_key.is_some() || self.jwt.is_some()
+    }
+
+    pub fn jwt(&self) -> Option<&JwtKeys> {
+        self.jwt.as_ref()
+    }
+
+    pub fn usage(&self) -> &Usage {
+        &self.usage
+    }
+
+    /// Whether the request carries the API key, counting it towards the
+    /// key's usage if so.
+    fn admit_api_key(&self, headers: &HeaderMap) -> bool {
+        let (Some(key), Some(client)) = (&self.api_key, &self.api_key_client) else {
+            return false;
+        };
+        let accepted = headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| bearer_token(headers))
+            .is_some_and(|presented| constant_time_eq(presented, key));
+        if accepted {
+            self.usage.record(client);
+        }
+        accepted
+    }
+}
+
+/// HMAC key pair for issuing and validating access tokens.
+#[derive(Clone)]
+pub struct JwtKeys {
+    encoding: Arc<EncodingKey>,
+    decoding: Arc<DecodingKey>,
+    ttl: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: Uuid,
+    username: String,
+    iat: u64,
+    exp: u64,
+}
+
+impl JwtKeys {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        Self {
+            encoding: Arc::new(EncodingKey::from_secret(secret)),
+            decoding: Arc::new(DecodingKey::from_secret(secret)),
+            ttl,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Signs an HS256 token for `user` that expires after the configured TTL.
+    pub fn issue(&self, user: &AuthUser) -> Result<String, AppError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let claims = Claims {
+            sub: user.id,
+            username: user.username.clone(),
+            iat: now,
+            exp: now + self.ttl.as_secs(),
+        };
+        jsonwebtoken::encode(&Header::default(), &claims, &self.encoding)
+            .map_err(|e| AppError::Internal(format!("failed to sign token: {e}")))
+    }
+
+    fn verify(&self, token: &str) -> Result<AuthUser, AppError> {
+        let data = jsonwebtoken::decode::<Claims>(token, &self.decoding, &Validation::default())
+            .map_err(|e| {
+                AppError::Unauthorized(match e.kind() {
+                    ErrorKind::ExpiredSignature => "token has expired".to_string(),
+                    _ => "invalid token".to_string(),
+                })
+            })?;
+        Ok(AuthUser {
+            id: data.claims.sub,
+            username: data.claims.username,
+        })
+    }
+}
+
+/// The user behind a validated bearer token.
+///
+/// As an extractor it rejects requests without a valid, unexpired token with
+/// `401 Unauthorized`.
+#[derive(Clone, Serialize, ToSchema)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub username: String,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    AuthConfig: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Already validated by `require_auth`.
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
+        let auth = AuthConfig::from_ref(state);
+        let jwt = auth.jwt().ok_or_else(|| {
+            AppError::Unauthorized("token authentication is not enabled".to_string())
+        })?;
+        let token = bearer_token(&parts.headers)
+            .ok_or_else(|| AppError::Unauthorized("missing bearer token".to_string()))?;
+        jwt.verify(token)
+    }
+}
+
+/// Rejects requests that carry neither the API key (as `X-API-Key` or a
+/// bearer token) nor a valid JWT bearer token with `401 Unauthorized`.
+/// Admitted credentials are counted in [`AuthConfig::usage`]; reads that
+/// needed none and carry no API key aren't.
+pub async fn require_auth(
+    State(auth): State<AuthConfig>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let is_read = request.method().is_safe();
+    if auth.admit_api_key(request.headers()) || (is_read && !auth.protect_reads) {
+        return Ok(next.run(request).await);
+    }
+
+    if let (Some(jwt), Some(token)) = (auth.jwt(), bearer_token(request.headers())) {
+        let user = jwt.verify(token)?;
+        auth.usage.record(&usage::user_client(&user.username));
+        request.extensions_mut().insert(user);
+        return Ok(next.run(request).await);
+    }
+
+    Err(AppError::Unauthorized(
+        "missing or invalid credentials".to_string(),
+    ))
+}
+
+/// Admits only requests carrying the API key, whatever the method; login
+/// tokens aren't enough. Without `API_KEY` every request is rejected.
+pub async fn require_api_key(
+    State(auth): State<AuthConfig>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if !auth.admit_api_key(request.headers()) {
+        return Err(AppError::Unauthorized(
+            "missing or invalid API key".to_string(),
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Checks `password` against an Argon2 PHC string such as `$argon2id$v=19$...`.
+pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppError> {
+    let parsed = PasswordHash::new(password_hash)
+        .map_err(|e| AppError::Internal(format!("stored password hash is invalid: {e}")))?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Compares without short-circuiting so response timing doesn't reveal how
+/// much of the key matched.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Entries kept at most; past this, new results are served uncached until
+/// old ones expire.
+const MAX_ENTRIES: usize = 1000;
+
+/// A short-lived in-process cache whose entries are all dropped at once
+/// whenever the underlying data changes.
+///
+/// Each [`invalidate`](Self::invalidate) bumps a generation counter. Readers
+/// take the generation before querying and pass it to
+/// [`insert`](Self::insert), which discards results computed before the
+/// latest invalidation so a slow read can't cache data a write has replaced.
+#[derive(Clone)]
+pub struct Cache<V> {
+    inner: Arc<RwLock<Inner<V>>>,
+    ttl: Duration,
+}
+
+struct Inner<V> {
+    generation: u64,
+    entries: HashMap<String, (Instant, V)>,
+}
+
+impl<V: Clone> Cache<V> {
+    /// A cache keeping entries for `ttl`; a zero `ttl` disables it.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Inner {
+                generation: 0,
+                entries: HashMap::new(),
+            })),
+            ttl,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    /// The generation to pass to [`insert`](Self::insert) for a result about
+    /// to be computed.
+    pub fn generation(&self) -> u64 {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .generation
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner
+            .entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Caches `value` unless the cache was invalidated after `generation`.
+    pub fn insert(&self, key: String, generation: u64, value: V) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        if inner.generation != generation {
+            return;
+        }
+
+        let now = Instant::now();
+        inner.entries.retain(|_, (expires_at, _)| *expires_at > now);
+        if inner.entries.len() < MAX_ENTRIES {
+            inner.entries.insert(key, (now + self.ttl, value));
+        }
+    }
+
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        inner.generation += 1;
+        inner.entries.clear();
+    }
+}
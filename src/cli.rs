@@ -0,0 +1,109 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use futures::TryStreamExt;
+use sqlx::{Pool, Postgres};
+
+use crate::{audit::Actor, error::AppError, export, import_row, Contact, CreateContact};
+
+/// Phone book REST API server and admin tool.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Every subcommand reads the same environment variables as the server.
+#[derive(Subcommand, Default)]
+pub enum Command {
+    /// Run the HTTP server (the default).
+    #[default]
+    Serve,
+    /// Apply pending database migrations and exit.
+    Migrate,
+    /// Insert a handful of sample contacts, skipping any whose phone number
+    /// is already taken.
+    Seed,
+    /// Write every contact to a CSV file, in the format of
+    /// `GET /contacts/export.csv`.
+    Export {
+        /// Where to write the CSV; an existing file is overwritten.
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+/// `(first_name, last_name, phone, company, tags)`; the numbers are in the
+/// 555-01xx range reserved for fiction.
+const SAMPLES: [(&str, &str, &str, &str, &[&str]); 6] = [
+    ("Alice", "Johnson", "+12025550101", "Acme", &["work"]),
+    ("Bob", "Smith", "+12025550102", "Acme", &["work", "golf"]),
+    ("Carol", "Williams", "+12025550103", "Globex", &["family"]),
+    ("Dave", "Brown", "+12025550104", "Initech", &["work"]),
+    ("Eve", "Davis", "+12025550105", "Globex", &["friends"]),
+    ("Frank", "Miller", "+12025550106", "Initech", &[]),
+];
+
+pub async fn seed(pool: &Pool<Postgres>) -> anyhow::Result<()> {
+    let actor = Actor::cli();
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+
+    for (first_name, last_name, phone, company, tags) in SAMPLES {
+        let contact = CreateContact {
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            phone: phone.to_string(),
+            email: Some(format!("{}@example.com", first_name.to_ascii_lowercase())),
+            birthday: None,
+            notes: None,
+            company: Some(company.to_string()),
+            country_code: None,
+            avatar_url: None,
+            address: None,
+            is_favorite: None,
+            phones: None,
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+        };
+        match import_row(&mut tx, &actor, &contact).await {
+            Ok(_) => inserted += 1,
+            Err(AppError::Conflict(_)) => {}
+            Err(e) => return Err(anyhow::anyhow!("seeding {first_name} {last_name}: {e}")),
+        }
+    }
+
+    tx.commit().await?;
+    println!(
+        "inserted {inserted} sample contacts, skipped {}",
+        SAMPLES.len() - inserted
+    );
+
+    Ok(())
+}
+
+pub async fn export_csv(pool: &Pool<Postgres>, out: &Path) -> anyhow::Result<()> {
+    let file =
+        File::create(out).with_context(|| format!("could not create `{}`", out.display()))?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&export::csv_header()?)?;
+
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE deleted_at IS NULL ORDER BY first_name, last_name, id",
+    )
+    .fetch(pool);
+    let mut exported = 0;
+    while let Some(contact) = contacts.try_next().await? {
+        writer.write_all(&export::csv_row(&contact)?)?;
+        exported += 1;
+    }
+    writer.flush()?;
+    println!("exported {exported} contacts to {}", out.display());
+
+    Ok(())
+}
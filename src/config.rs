@@ -0,0 +1,228 @@
+use std::{io::IsTerminal, net::SocketAddr, time::Duration};
+
+use anyhow::Context;
+use serde::{de, Deserialize, Deserializer};
+use utoipa::ToSchema;
+
+/// Every setting the server reads from the environment, loaded once at
+/// startup. Each field is read from the upper-cased variable of the same
+/// name, e.g. `db_max_connections` from `DB_MAX_CONNECTIONS`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// PostgreSQL connection string; the only required setting.
+    pub database_url: String,
+    #[serde(deserialize_with = "socket_addr")]
+    pub bind_addr: SocketAddr,
+
+    pub db_max_connections: u32,
+    /// Connections kept open even when idle.
+    pub db_min_connections: u32,
+    /// How long a request waits for a free connection.
+    pub db_acquire_timeout_secs: u64,
+    /// Idle connections are closed after this long; `0` keeps them forever.
+    pub db_idle_timeout_secs: u64,
+    /// Startup attempts to connect and migrate.
+    pub db_connect_attempts: u32,
+    /// Apply pending migrations at startup; when off, only `migrate` does.
+    pub auto_migrate: bool,
+    /// Statements taking longer than this are logged as warnings; `0`
+    /// disables the check.
+    pub slow_query_ms: u64,
+    /// Defaults to [`LogFormat::Pretty`] on a terminal and
+    /// [`LogFormat::Json`] otherwise.
+    pub log_format: Option<LogFormat>,
+
+    /// Order of `GET /contacts` when the request has no `sort_by`.
+    pub default_sort: SortField,
+
+    /// Key accepted as `X-API-Key` or a bearer token.
+    pub api_key: Option<String>,
+    /// Also require credentials for `GET`, `HEAD`, and `OPTIONS`.
+    pub api_key_protect_reads: bool,
+    /// HMAC secret for login tokens; without it `/auth/login` is disabled.
+    pub jwt_secret: Option<String>,
+    pub jwt_ttl_secs: u64,
+
+    /// Requests per client IP per minute; `0` disables rate limiting.
+    pub rate_limit_per_minute: u32,
+    /// Comma-separated proxy addresses whose `X-Forwarded-For` and
+    /// `X-Real-IP` headers name the client for rate limiting.
+    pub trusted_proxies: Option<String>,
+    pub max_body_bytes: usize,
+    pub max_import_body_bytes: usize,
+    pub request_timeout_secs: u64,
+    pub import_timeout_secs: u64,
+
+    /// Comma-separated origins allowed to call the API from a browser.
+    pub cors_allowed_origins: Option<String>,
+    /// Allow any origin when no allow-list is given, for development.
+    pub cors_permissive: bool,
+
+    /// How long a `GET /contacts` page is served from memory; `0` disables
+    /// the cache.
+    pub list_cache_ttl_secs: u64,
+    /// Redis server caching single contacts and search results for every
+    /// instance; unset disables it.
+    pub redis_url: Option<String>,
+    pub redis_cache_ttl_secs: u64,
+
+    /// Also refuse a contact whose name (ignoring case) and phone number
+    /// match an existing one.
+    pub strict_dedup: bool,
+    /// Refuse every change to contacts, e.g. during database maintenance.
+    pub read_only: bool,
+
+    /// How long soft-deleted contacts stay restorable before
+    /// `DELETE /contacts/trash/purge` removes them.
+    pub trash_retention_days: u32,
+
+    /// Endpoint that receives a `POST` for every contact change.
+    pub webhook_url: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: String::new(),
+            bind_addr: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            db_max_connections: 5,
+            db_min_connections: 0,
+            db_acquire_timeout_secs: 30,
+            db_idle_timeout_secs: 600,
+            db_connect_attempts: 10,
+            auto_migrate: true,
+            slow_query_ms: 500,
+            log_format: None,
+            default_sort: SortField::FirstName,
+            api_key: None,
+            api_key_protect_reads: false,
+            jwt_secret: None,
+            jwt_ttl_secs: 3600,
+            rate_limit_per_minute: 100,
+            trusted_proxies: None,
+            max_body_bytes: 1024 * 1024,
+            max_import_body_bytes: 16 * 1024 * 1024,
+            request_timeout_secs: 30,
+            import_timeout_secs: 300,
+            cors_allowed_origins: None,
+            cors_permissive: false,
+            list_cache_ttl_secs: 5,
+            redis_url: None,
+            redis_cache_ttl_secs: 60,
+            strict_dedup: false,
+            read_only: false,
+            trash_retention_days: 30,
+            webhook_url: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line, for log aggregation.
+    Json,
+    /// Human-readable lines, for local development.
+    Pretty,
+}
+
+/// A column `GET /contacts` can be sorted by.
+#[derive(Deserialize, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    /// Then by last name.
+    FirstName,
+    /// Then by first name.
+    LastName,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// Parses `BIND_ADDR`, naming it in the error, which serde's own
+/// `SocketAddr` impl doesn't.
+fn socket_addr<'de, D>(deserializer: D) -> Result<SocketAddr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(|_| {
+        de::Error::custom(format!("BIND_ADDR `{value}` is not a valid socket address"))
+    })
+}
+
+impl Config {
+    /// Reads and validates the configuration. Unset variables take their
+    /// defaults; variables set to an empty string count as unset.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let vars = std::env::vars().filter(|(_, value)| !value.is_empty());
+        let config: Config = envy::from_iter(vars).context("invalid configuration")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(!self.database_url.is_empty(), "DATABASE_URL must be set");
+        anyhow::ensure!(
+            self.db_max_connections > 0,
+            "DB_MAX_CONNECTIONS must be at least 1"
+        );
+        anyhow::ensure!(
+            self.db_min_connections <= self.db_max_connections,
+            "DB_MIN_CONNECTIONS ({}) must not exceed DB_MAX_CONNECTIONS ({})",
+            self.db_min_connections,
+            self.db_max_connections
+        );
+        anyhow::ensure!(
+            self.db_acquire_timeout_secs > 0,
+            "DB_ACQUIRE_TIMEOUT_SECS must be at least 1"
+        );
+        anyhow::ensure!(
+            self.db_connect_attempts > 0,
+            "DB_CONNECT_ATTEMPTS must be at least 1"
+        );
+        anyhow::ensure!(
+            self.request_timeout_secs > 0 && self.import_timeout_secs > 0,
+            "REQUEST_TIMEOUT_SECS and IMPORT_TIMEOUT_SECS must be at least 1"
+        );
+        anyhow::ensure!(
+            self.redis_cache_ttl_secs > 0,
+            "REDIS_CACHE_TTL_SECS must be at least 1"
+        );
+        Ok(())
+    }
+
+    pub fn log_format(&self) -> LogFormat {
+        self.log_format.unwrap_or_else(|| {
+            if std::io::stdout().is_terminal() {
+                LogFormat::Pretty
+            } else {
+                LogFormat::Json
+            }
+        })
+    }
+
+    pub fn list_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.list_cache_ttl_secs)
+    }
+
+    pub fn redis_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.redis_cache_ttl_secs)
+    }
+
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.db_acquire_timeout_secs)
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.db_idle_timeout_secs > 0).then(|| Duration::from_secs(self.db_idle_timeout_secs))
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    pub fn import_timeout(&self) -> Duration {
+        Duration::from_secs(self.import_timeout_secs)
+    }
+}
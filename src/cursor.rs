@@ -0,0 +1,39 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use uuid::Uuid;
+
+use crate::{error::AppError, Contact};
+
+/// Position just after the last contact of a page in the default
+/// `(first_name, last_name, id)` order.
+pub struct Cursor {
+    pub first_name: String,
+    pub last_name: String,
+    pub id: Uuid,
+}
+
+/// Postgres text can't contain NUL, so it can't appear inside a name.
+const SEPARATOR: char = '\0';
+
+/// Opaque to clients: URL-safe base64 of the sort key.
+pub fn encode(contact: &Contact) -> String {
+    URL_SAFE_NO_PAD.encode(format!(
+        "{}{SEPARATOR}{}{SEPARATOR}{}",
+        contact.id, contact.first_name, contact.last_name
+    ))
+}
+
+pub fn decode(cursor: &str) -> Result<Cursor, AppError> {
+    let invalid = || AppError::BadRequest("`after` is not a valid cursor".to_string());
+
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let mut parts = text.splitn(3, SEPARATOR);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(id), Some(first_name), Some(last_name)) => Ok(Cursor {
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        }),
+        _ => Err(invalid()),
+    }
+}
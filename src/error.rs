@@ -0,0 +1,197 @@
+use std::{collections::BTreeMap, fmt};
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::phone::ValidationError;
+
+/// How long clients are told to wait when every database connection was
+/// busy for the whole acquire timeout.
+const POOL_TIMEOUT_RETRY_AFTER_SECS: u64 = 5;
+
+/// Error type shared by all handlers.
+///
+/// Every variant renders as `{ "error": "...", "code": "..." }` with the
+/// matching HTTP status, so clients can branch on `code` instead of parsing
+/// free-form messages.
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    /// A path segment that should be a UUID isn't one.
+    InvalidUuid(String),
+    Unauthorized(String),
+    NotFound(String),
+    /// The path exists but doesn't support the request method.
+    MethodNotAllowed(String),
+    NotAcceptable(String),
+    Conflict(String),
+    PreconditionFailed(String),
+    Validation(String),
+    /// The client exceeded its request quota and may retry after this many
+    /// seconds.
+    RateLimited {
+        retry_after_secs: u64,
+    },
+    /// The server can't handle the request right now; the client may retry
+    /// after this many seconds.
+    Unavailable {
+        message: String,
+        retry_after_secs: u64,
+    },
+    /// Per-field validation failures, keyed by field name.
+    InvalidFields(BTreeMap<String, String>),
+    Internal(String),
+}
+
+/// JSON body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Human-readable message.
+    error: String,
+    /// Stable machine-readable code, e.g. `not_found`.
+    code: &'static str,
+    /// Per-field messages, present only for field validation failures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<BTreeMap<String, String>>,
+}
+
+impl AppError {
+    fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) | AppError::InvalidUuid(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            AppError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::Validation(_) | AppError::InvalidFields(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad_request",
+            AppError::InvalidUuid(_) => "invalid_uuid",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::NotFound(_) => "not_found",
+            AppError::MethodNotAllowed(_) => "method_not_allowed",
+            AppError::NotAcceptable(_) => "not_acceptable",
+            AppError::Conflict(_) => "conflict",
+            AppError::PreconditionFailed(_) => "precondition_failed",
+            AppError::Validation(_) | AppError::InvalidFields(_) => "validation_failed",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::Unavailable { .. } => "service_unavailable",
+            AppError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::InvalidFields(errors) => {
+                let details: Vec<String> = errors
+                    .iter()
+                    .map(|(field, message)| format!("{field}: {message}"))
+                    .collect();
+                f.write_str(&details.join("; "))
+            }
+            AppError::RateLimited { retry_after_secs } => write!(
+                f,
+                "too many requests; retry after {retry_after_secs} seconds"
+            ),
+            AppError::Unavailable { message, .. } => f.write_str(message),
+            AppError::BadRequest(message)
+            | AppError::InvalidUuid(message)
+            | AppError::Unauthorized(message)
+            | AppError::NotFound(message)
+            | AppError::MethodNotAllowed(message)
+            | AppError::NotAcceptable(message)
+            | AppError::Conflict(message)
+            | AppError::PreconditionFailed(message)
+            | AppError::Validation(message)
+            | AppError::Internal(message) => f.write_str(message),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+        let mut headers = HeaderMap::new();
+        match &self {
+            AppError::Unauthorized(_) => {
+                headers.insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            }
+            AppError::RateLimited { retry_after_secs }
+            | AppError::Unavailable {
+                retry_after_secs, ..
+            } => {
+                headers.insert(header::RETRY_AFTER, HeaderValue::from(*retry_after_secs));
+            }
+            _ => {}
+        }
+        let (error, errors) = match self {
+            AppError::InvalidFields(errors) => ("validation failed".to_string(), Some(errors)),
+            other => (other.to_string(), None),
+        };
+
+        (
+            status,
+            headers,
+            Json(ErrorBody {
+                error,
+                code,
+                errors,
+            }),
+        )
+            .into_response()
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => AppError::NotFound("Contact not found".to_string()),
+            sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+                let message = match db_err.constraint() {
+                    Some("contacts_phone_key") => {
+                        "A contact with this phone number already exists".to_string()
+                    }
+                    Some("contacts_person_key") => {
+                        "A contact with this name and phone number already exists; this is \
+                         likely the same person"
+                            .to_string()
+                    }
+                    _ => db_err.message().to_string(),
+                };
+                AppError::Conflict(message)
+            }
+            // The pool is saturated, which passes once load drops; it's not a
+            // bug worth a 500.
+            sqlx::Error::PoolTimedOut => AppError::Unavailable {
+                message: "all database connections are busy; try again shortly".to_string(),
+                retry_after_secs: POOL_TIMEOUT_RETRY_AFTER_SECS,
+            },
+            _ => AppError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<ValidationError> for AppError {
+    fn from(e: ValidationError) -> Self {
+        AppError::Validation(e.to_string())
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    watch,
+};
+
+use crate::Contact;
+
+/// Events a subscriber may fall behind by before it starts missing some.
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Clone, Copy)]
+pub enum Event {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl Event {
+    fn as_str(self) -> &'static str {
+        match self {
+            Event::Created => "contact.created",
+            Event::Updated => "contact.updated",
+            Event::Deleted => "contact.deleted",
+            Event::Restored => "contact.restored",
+        }
+    }
+}
+
+/// A committed change, as sent to webhooks and event-stream clients.
+#[derive(Serialize)]
+pub struct ContactEvent {
+    /// `contact.created`, `contact.updated`, `contact.deleted`, or
+    /// `contact.restored`.
+    pub event: &'static str,
+    pub contact: Contact,
+}
+
+/// Fans committed contact changes out to every live subscriber.
+#[derive(Clone)]
+pub struct Events {
+    sender: broadcast::Sender<Arc<ContactEvent>>,
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let (closed, _) = watch::channel(false);
+        Self {
+            sender,
+            closed: Arc::new(closed),
+        }
+    }
+
+    /// Call only after the change has been committed. Never blocks; with no
+    /// subscribers the event is simply discarded.
+    pub fn publish(&self, event: Event, contact: &Contact) {
+        let _ = self.sender.send(Arc::new(ContactEvent {
+            event: event.as_str(),
+            contact: contact.clone(),
+        }));
+    }
+
+    pub fn subscribe(&self) -> Subscription {
+        Subscription {
+            events: self.sender.subscribe(),
+            closed: self.closed.subscribe(),
+        }
+    }
+
+    /// Ends every subscription, so open event streams don't hold up a
+    /// graceful shutdown.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+pub enum Received {
+    Event(Arc<ContactEvent>),
+    /// The subscriber fell behind and this many events were skipped.
+    Lagged(u64),
+}
+
+pub struct Subscription {
+    events: broadcast::Receiver<Arc<ContactEvent>>,
+    closed: watch::Receiver<bool>,
+}
+
+impl Subscription {
+    /// Waits for the next event; `None` once the server is shutting down.
+    pub async fn recv(&mut self) -> Option<Received> {
+        tokio::select! {
+            result = self.events.recv() => match result {
+                Ok(event) => Some(Received::Event(event)),
+                Err(RecvError::Lagged(skipped)) => Some(Received::Lagged(skipped)),
+                Err(RecvError::Closed) => None,
+            },
+            _ = self.closed.wait_for(|closed| *closed) => None,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::Contact;
+
+pub const CSV_HEADER: [&str; 7] = [
+    "id",
+    "first_name",
+    "last_name",
+    "phone",
+    "email",
+    "created_at",
+    "updated_at",
+];
+
+/// Encodes a single CSV record, including the trailing newline.
+fn csv_record<I, T>(fields: I) -> Result<Vec<u8>, csv::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer.write_record(fields)?;
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+pub fn csv_header() -> Result<Vec<u8>, csv::Error> {
+    csv_record(CSV_HEADER)
+}
+
+pub fn csv_row(contact: &Contact) -> Result<Vec<u8>, csv::Error> {
+    csv_record([
+        contact.id.to_string(),
+        contact.first_name.clone(),
+        contact.last_name.clone(),
+        contact.phone.clone(),
+        contact.email.clone().unwrap_or_default(),
+        contact.created_at.to_rfc3339(),
+        contact.updated_at.to_rfc3339(),
+    ])
+}
+
+/// One element of the pretty-printed JSON array written by
+/// `GET /contacts/export.json`, preceded by the separator it needs.
+pub fn json_item(contact: &Contact, first: bool) -> Result<Vec<u8>, serde_json::Error> {
+    // JSON strings escape their newlines, so every line break is formatting.
+    let pretty = serde_json::to_string_pretty(contact)?;
+    let mut item = String::from(if first { "\n" } else { ",\n" });
+    for (index, line) in pretty.lines().enumerate() {
+        if index > 0 {
+            item.push('\n');
+        }
+        item.push_str("  ");
+        item.push_str(line);
+    }
+    Ok(item.into_bytes())
+}
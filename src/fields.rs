@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::IntoParams;
+
+use crate::{error::AppError, phone::PhoneFormat};
+
+/// Every top-level key of a serialized `Contact`.
+const CONTACT_FIELDS: [&str; 20] = [
+    "id",
+    "first_name",
+    "last_name",
+    "phone",
+    "phone_type",
+    "email",
+    "birthday",
+    "notes",
+    "company",
+    "country_code",
+    "avatar_url",
+    "address",
+    "is_favorite",
+    "last_contacted_at",
+    "created_at",
+    "updated_at",
+    "version",
+    "deleted_at",
+    "phones",
+    "tags",
+];
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldSelection {
+    /// Comma-separated contact fields to return, e.g. `id,first_name,phone`;
+    /// every field when omitted.
+    fields: Option<String>,
+    /// How to write phone numbers (default `e164`).
+    #[serde(rename = "format", default)]
+    #[param(rename = "format", inline)]
+    pub phone_format: PhoneFormat,
+}
+
+impl FieldSelection {
+    /// The requested field names, or `None` for whole contacts.
+    pub fn parse(&self) -> Result<Option<Vec<&str>>, AppError> {
+        let Some(fields) = &self.fields else {
+            return Ok(None);
+        };
+
+        let fields: Vec<&str> = fields
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .collect();
+        if fields.is_empty() {
+            return Err(AppError::BadRequest(
+                "`fields` must name at least one field".to_string(),
+            ));
+        }
+        let unknown: Vec<&str> = fields
+            .iter()
+            .copied()
+            .filter(|field| !CONTACT_FIELDS.contains(field))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "unknown fields: {}; available fields are {}",
+                unknown.join(", "),
+                CONTACT_FIELDS.join(", ")
+            )));
+        }
+
+        Ok(Some(fields))
+    }
+}
+
+/// Serializes `value`, keeping only `fields` of the object itself or, with
+/// `nested`, of every object in that array-valued key.
+pub fn select<T: Serialize>(
+    value: &T,
+    fields: &[&str],
+    nested: Option<&str>,
+) -> Result<Value, AppError> {
+    let mut value = serde_json::to_value(value)
+        .map_err(|e| AppError::Internal(format!("failed to serialize contact: {e}")))?;
+
+    let retain = |object: &mut Value| {
+        if let Value::Object(object) = object {
+            object.retain(|key, _| fields.contains(&key.as_str()));
+        }
+    };
+    match nested {
+        Some(key) => {
+            if let Some(Value::Array(items)) = value.get_mut(key) {
+                items.iter_mut().for_each(retain);
+            }
+        }
+        None => retain(&mut value),
+    }
+
+    Ok(value)
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A matched part of a string, as character offsets with `end` exclusive.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Where any of `terms` occurs in `text`, ignoring case the way `ILIKE`
+/// does. Overlapping matches are merged, so the spans are disjoint and in
+/// order.
+pub fn find(text: &str, terms: &[&str]) -> Vec<Span> {
+    let text: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    for term in terms {
+        let term: Vec<char> = term.trim().chars().collect();
+        if term.is_empty() || term.len() > text.len() {
+            continue;
+        }
+        let mut start = 0;
+        while start + term.len() <= text.len() {
+            let window = &text[start..start + term.len()];
+            if window.iter().zip(&term).all(|(a, b)| same_letter(*a, *b)) {
+                spans.push(Span {
+                    start,
+                    end: start + term.len(),
+                });
+                start += term.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+
+    spans.sort_by_key(|span| span.start);
+    let mut merged: Vec<Span> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+fn same_letter(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use axum::http::{HeaderMap, HeaderName};
+use sqlx::{PgConnection, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub const HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// How long a key is remembered, as a Postgres interval.
+const TTL: &str = "24 hours";
+
+const MAX_KEY_LEN: usize = 255;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Reads the optional `Idempotency-Key` request header.
+pub fn key_from_headers(headers: &HeaderMap) -> Result<Option<&str>, AppError> {
+    let Some(value) = headers.get(&HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| AppError::BadRequest("Idempotency-Key must be printable ASCII".to_string()))?
+        .trim();
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Idempotency-Key must be between 1 and {MAX_KEY_LEN} characters"
+        )));
+    }
+    Ok(Some(key))
+}
+
+/// Returns the contact created under `key`, if it was used in the last 24
+/// hours.
+///
+/// Takes a transaction-scoped lock on the key first, so a concurrent retry
+/// with the same key waits for this transaction instead of inserting a second
+/// contact.
+pub async fn lock_and_lookup(
+    conn: &mut PgConnection,
+    key: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(key)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query_scalar(
+        "SELECT contact_id FROM idempotency_keys WHERE key = $1 AND created_at > NOW() - $2::INTERVAL",
+    )
+    .bind(key)
+    .bind(TTL)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+/// Records that `key` created `contact_id`, replacing an expired entry.
+pub async fn remember(
+    conn: &mut PgConnection,
+    key: &str,
+    contact_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO idempotency_keys (key, contact_id)
+        VALUES ($1, $2)
+        ON CONFLICT (key) DO UPDATE
+        SET contact_id = EXCLUDED.contact_id, created_at = NOW()
+        "#,
+    )
+    .bind(key)
+    .bind(contact_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Periodically deletes expired keys so the table doesn't grow without bound.
+pub fn spawn_sweeper(pool: Pool<Postgres>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let result = sqlx::query(
+                "DELETE FROM idempotency_keys WHERE created_at <= NOW() - $1::INTERVAL",
+            )
+            .bind(TTL)
+            .execute(&pool)
+            .await;
+            match result {
+                Ok(done) if done.rows_affected() > 0 => {
+                    tracing::debug!(deleted = done.rows_affected(), "expired idempotency keys")
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "failed to expire idempotency keys"),
+            }
+        }
+    });
+}
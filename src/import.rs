@@ -0,0 +1,54 @@
+use crate::CreateContact;
+
+const REQUIRED_COLUMNS: [&str; 3] = ["first_name", "last_name", "phone"];
+
+/// A parsed CSV data row, tagged with its 1-based line number in the file.
+pub struct CsvRow {
+    pub line: u64,
+    pub contact: Result<CreateContact, String>,
+}
+
+/// Parses an uploaded CSV file into contacts.
+///
+/// The header row must name at least `first_name`, `last_name`, and `phone`;
+/// any other columns (such as those produced by the CSV export) are ignored.
+/// Rows that fail to parse are returned as errors rather than aborting.
+pub fn parse_csv(data: &[u8]) -> Result<Vec<CsvRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("invalid CSV header: {e}"))?
+        .clone();
+    let missing: Vec<&str> = REQUIRED_COLUMNS
+        .into_iter()
+        .filter(|column| !headers.iter().any(|h| h == *column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "CSV header is missing required columns: {}",
+            missing.join(", ")
+        ));
+    }
+
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let row = match result {
+            Ok(record) => CsvRow {
+                line: record.position().map_or(0, |p| p.line()),
+                contact: record
+                    .deserialize::<CreateContact>(Some(&headers))
+                    .map_err(|e| e.to_string()),
+            },
+            Err(e) => CsvRow {
+                line: e.position().map_or(0, |p| p.line()),
+                contact: Err(e.to_string()),
+            },
+        };
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
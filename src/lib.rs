@@ -0,0 +1,5053 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    net::SocketAddr,
+    pin::pin,
+    slice,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use axum::{
+    async_trait,
+    body::{Body, Bytes},
+    extract::{
+        DefaultBodyLimit, FromRef, FromRequestParts, MatchedPath, Multipart, OriginalUri, Path,
+        Query, RawQuery, State,
+    },
+    http::{
+        header, request::Parts, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode,
+    },
+    middleware,
+    response::{
+        sse::{self, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put},
+    BoxError, Json, Router,
+};
+// Unlike axum's `Query`, this one collects repeated keys such as `?tag=a&tag=b`.
+use axum_extra::extract::Query as MultiQuery;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser;
+use futures::{Stream, TryStreamExt};
+use log::LevelFilter;
+use metrics_exporter_prometheus::PrometheusHandle;
+use phonenumber::country;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgConnectOptions, PgPoolOptions},
+    Acquire, ConnectOptions, FromRow, PgConnection, Pool, Postgres, QueryBuilder, Row,
+};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::CorsLayer,
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    timeout::TimeoutLayer,
+    trace::{DefaultOnResponse, TraceLayer},
+    LatencyUnit,
+};
+use tracing::{Level, Span};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use url::Url;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+use uuid::Uuid;
+
+mod address;
+mod audit;
+mod auth;
+mod cache;
+mod cli;
+mod config;
+mod cursor;
+mod error;
+mod events;
+mod export;
+mod fields;
+mod highlight;
+mod idempotency;
+mod import;
+mod negotiate;
+mod openapi;
+mod phone;
+mod qr;
+mod rate_limit;
+mod redis_cache;
+mod telemetry;
+mod usage;
+mod vcard;
+mod webhook;
+
+use address::{Address, AddressColumns};
+use audit::{Action, Actor, AuditEntry};
+use auth::{AuthConfig, AuthUser, JwtKeys};
+use cache::Cache;
+use cli::{Cli, Command};
+pub use config::{Config, LogFormat, SortField};
+use error::AppError;
+use events::{Event, Events, Received};
+use fields::FieldSelection;
+use negotiate::Format;
+use phone::{normalize_phone, PhoneFormat, PhoneType};
+use redis_cache::RedisCache;
+
+/// Prefix of the current API version. Health probes, metrics, and the docs
+/// stay at the root.
+const API_PREFIX: &str = "/v1";
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+
+/// Whether `GET /contacts` was answered from the list cache: `HIT` or `MISS`.
+const X_CACHE: HeaderName = HeaderName::from_static("x-cache");
+
+/// `Retry-After` sent with refused writes in read-only mode.
+const READ_ONLY_RETRY_AFTER_SECS: u64 = 60;
+
+#[derive(Clone)]
+struct AppState {
+    config: Arc<Config>,
+    db_pool: Pool<Postgres>,
+    auth: AuthConfig,
+    metrics: PrometheusHandle,
+    events: Events,
+    /// Recent `GET /contacts` pages, keyed by query string.
+    list_cache: Cache<Arc<PaginatedContacts>>,
+    /// Single contacts and search results shared with other instances, when
+    /// `REDIS_URL` is set.
+    redis: Option<RedisCache>,
+}
+
+impl FromRef<AppState> for AuthConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth.clone()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+struct Contact {
+    id: Uuid,
+    first_name: String,
+    last_name: String,
+    phone: String,
+    /// Derived from `phone` whenever a contact is read; not stored.
+    #[sqlx(rename = "phone", try_from = "String")]
+    #[serde(default)]
+    phone_type: PhoneType,
+    email: Option<String>,
+    birthday: Option<NaiveDate>,
+    notes: Option<String>,
+    company: Option<String>,
+    /// ISO 3166-1 alpha-2 code, upper-case.
+    country_code: Option<String>,
+    /// Absolute `http` or `https` URL of a profile picture.
+    avatar_url: Option<String>,
+    /// Omitted when no part of the address is set.
+    #[sqlx(flatten, try_from = "AddressColumns")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<Address>,
+    is_favorite: bool,
+    /// Set by `POST /contacts/{id}/touch`; `null` if never contacted.
+    last_contacted_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    /// Incremented on every update; used for optimistic concurrency control.
+    version: i32,
+    /// Set when the contact is soft-deleted; such contacts are hidden from
+    /// every endpoint except an `include_deleted` listing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<Utc>>,
+    /// Every number for the contact, including the primary `phone`. Loaded
+    /// from `phone_numbers` by `load_related`.
+    #[sqlx(skip)]
+    #[serde(default)]
+    phones: Vec<PhoneNumber>,
+    /// Lower-cased tag names in alphabetical order.
+    #[sqlx(skip)]
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+struct PhoneNumber {
+    label: String,
+    number: String,
+}
+
+impl Contact {
+    /// Rewrites every number for display; only for responses, since stored
+    /// numbers must stay E.164.
+    fn format_phones(&mut self, format: PhoneFormat) {
+        self.phone = format.apply(&self.phone);
+        for phone in &mut self.phones {
+            phone.number = format.apply(&phone.number);
+        }
+    }
+
+    /// The contact as a create request, so a restored backup goes through
+    /// the same checks and normalization as a new contact.
+    fn to_create(&self) -> CreateContact {
+        CreateContact {
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+            phone: self.phone.clone(),
+            email: self.email.clone(),
+            birthday: self.birthday,
+            notes: self.notes.clone(),
+            company: self.company.clone(),
+            country_code: self.country_code.clone(),
+            avatar_url: self.avatar_url.clone(),
+            address: self.address.clone(),
+            is_favorite: Some(self.is_favorite),
+            phones: Some(self.phones.clone()),
+            tags: Some(self.tags.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateContact {
+    first_name: String,
+    last_name: String,
+    phone: String,
+    email: Option<String>,
+    birthday: Option<NaiveDate>,
+    notes: Option<String>,
+    /// Blank values are stored as no company.
+    company: Option<String>,
+    /// ISO 3166-1 alpha-2 code such as `NL`. Numbers without a leading `+`
+    /// are read as national numbers of this country.
+    country_code: Option<String>,
+    /// Absolute `http` or `https` URL of a profile picture.
+    avatar_url: Option<String>,
+    /// Blank parts are stored as unset.
+    address: Option<Address>,
+    /// Defaults to `false`.
+    is_favorite: Option<bool>,
+    phones: Option<Vec<PhoneNumber>>,
+    /// Tags that don't exist yet are created.
+    tags: Option<Vec<String>>,
+}
+
+/// Distinguishes an absent key (`None`) from an explicit `null`
+/// (`Some(None)`) for `#[serde(default)]` fields.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Omitted fields are left unchanged. `null` clears `email`, `birthday`,
+/// `notes`, `company`, `country_code`, `avatar_url`, and `address`; other
+/// fields can't be cleared.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+struct UpdateContact {
+    first_name: Option<String>,
+    last_name: Option<String>,
+    phone: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    email: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<NaiveDate>)]
+    birthday: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    notes: Option<Option<String>>,
+    /// An empty string clears the company, as does `null`.
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    company: Option<Option<String>>,
+    /// Also applies to the numbers given in the same request; numbers that
+    /// are already stored aren't reinterpreted.
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    country_code: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    avatar_url: Option<Option<String>>,
+    /// Replaces the whole address; parts left out are cleared.
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<Address>)]
+    address: Option<Option<Address>>,
+    is_favorite: Option<bool>,
+    phones: Option<Vec<PhoneNumber>>,
+    /// Replaces every tag on the contact.
+    tags: Option<Vec<String>>,
+    /// When set, the update only applies if the stored version still matches.
+    version: Option<i32>,
+}
+
+const MAX_NAME_LEN: usize = 100;
+const MAX_TAG_LEN: usize = 50;
+const MAX_NOTES_LEN: usize = 10_000;
+const MAX_ADDRESS_PART_LEN: usize = 200;
+
+/// Validation failures keyed by field name.
+type FieldErrors = BTreeMap<String, String>;
+
+/// Records an error for `field` if `value` is blank or longer than
+/// `MAX_NAME_LEN` characters once trimmed.
+fn check_name(errors: &mut FieldErrors, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        errors.insert(field.to_string(), "must not be empty".to_string());
+    } else if value.chars().count() > MAX_NAME_LEN {
+        errors.insert(
+            field.to_string(),
+            format!("must be at most {MAX_NAME_LEN} characters"),
+        );
+    }
+}
+
+fn check_phone(errors: &mut FieldErrors, value: &str) {
+    if value.trim().is_empty() {
+        errors.insert("phone".to_string(), "must not be empty".to_string());
+    }
+}
+
+fn check_phones(errors: &mut FieldErrors, phones: &[PhoneNumber]) {
+    for (index, phone) in phones.iter().enumerate() {
+        if phone.label.trim().is_empty() {
+            errors.insert(format!("phones[{index}].label"), "must not be empty".to_string());
+        }
+        if phone.number.trim().is_empty() {
+            errors.insert(format!("phones[{index}].number"), "must not be empty".to_string());
+        }
+    }
+}
+
+fn check_email(errors: &mut FieldErrors, value: &str) {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    let email = EMAIL.get_or_init(|| {
+        Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").expect("email pattern is valid")
+    });
+
+    if !email.is_match(value.trim()) {
+        errors.insert("email".to_string(), "must be a valid email address".to_string());
+    }
+}
+
+fn check_tags(errors: &mut FieldErrors, tags: &[String]) {
+    for (index, tag) in tags.iter().enumerate() {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            errors.insert(format!("tags[{index}]"), "must not be empty".to_string());
+        } else if tag.chars().count() > MAX_TAG_LEN {
+            errors.insert(
+                format!("tags[{index}]"),
+                format!("must be at most {MAX_TAG_LEN} characters"),
+            );
+        }
+    }
+}
+
+fn check_company(errors: &mut FieldErrors, value: &str) {
+    if value.trim().chars().count() > MAX_NAME_LEN {
+        errors.insert(
+            "company".to_string(),
+            format!("must be at most {MAX_NAME_LEN} characters"),
+        );
+    }
+}
+
+/// Trims a company name, treating a blank one as none.
+fn normalize_company(company: Option<&str>) -> Option<&str> {
+    company.map(str::trim).filter(|company| !company.is_empty())
+}
+
+fn check_country_code(errors: &mut FieldErrors, value: &str) {
+    if phone::parse_region(value).is_none() {
+        errors.insert(
+            "country_code".to_string(),
+            "must be an ISO 3166-1 alpha-2 country code such as NL".to_string(),
+        );
+    }
+}
+
+/// Upper-cases a country code already accepted by `check_country_code`.
+fn normalize_country_code(code: Option<&str>) -> Option<String> {
+    code.map(|code| code.trim().to_ascii_uppercase())
+}
+
+/// Re-serializes a URL accepted by `check_avatar_url`. Parsing
+/// percent-encodes non-ASCII paths and punycodes hosts, so the result is
+/// always usable as a `Location` header.
+fn normalize_avatar_url(url: Option<&str>) -> Option<String> {
+    url.map(|url| Url::parse(url.trim()).map_or_else(|_| url.trim().to_string(), String::from))
+}
+
+fn check_avatar_url(errors: &mut FieldErrors, value: &str) {
+    let valid = Url::parse(value.trim()).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+    if !valid {
+        errors.insert(
+            "avatar_url".to_string(),
+            "must be an absolute http or https URL".to_string(),
+        );
+    }
+}
+
+fn check_address(errors: &mut FieldErrors, address: &Address) {
+    for (part, value) in address.parts() {
+        if value.is_some_and(|value| value.trim().chars().count() > MAX_ADDRESS_PART_LEN) {
+            errors.insert(
+                format!("address.{part}"),
+                format!("must be at most {MAX_ADDRESS_PART_LEN} characters"),
+            );
+        }
+    }
+}
+
+fn check_notes(errors: &mut FieldErrors, value: &str) {
+    if value.chars().count() > MAX_NOTES_LEN {
+        errors.insert(
+            "notes".to_string(),
+            format!("must be at most {MAX_NOTES_LEN} characters"),
+        );
+    }
+}
+
+fn check_birthday(errors: &mut FieldErrors, value: NaiveDate) {
+    if value > Utc::now().date_naive() {
+        errors.insert("birthday".to_string(), "must not be in the future".to_string());
+    }
+}
+
+fn invalid_fields(errors: FieldErrors) -> Result<(), AppError> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::InvalidFields(errors))
+    }
+}
+
+impl CreateContact {
+    fn field_errors(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        check_name(&mut errors, "first_name", &self.first_name);
+        check_name(&mut errors, "last_name", &self.last_name);
+        check_phone(&mut errors, &self.phone);
+        if let Some(phones) = &self.phones {
+            check_phones(&mut errors, phones);
+        }
+        if let Some(tags) = &self.tags {
+            check_tags(&mut errors, tags);
+        }
+        if let Some(email) = &self.email {
+            check_email(&mut errors, email);
+        }
+        if let Some(birthday) = self.birthday {
+            check_birthday(&mut errors, birthday);
+        }
+        if let Some(notes) = &self.notes {
+            check_notes(&mut errors, notes);
+        }
+        if let Some(company) = &self.company {
+            check_company(&mut errors, company);
+        }
+        if let Some(country_code) = &self.country_code {
+            check_country_code(&mut errors, country_code);
+        }
+        if let Some(avatar_url) = &self.avatar_url {
+            check_avatar_url(&mut errors, avatar_url);
+        }
+        if let Some(address) = &self.address {
+            check_address(&mut errors, address);
+        }
+        errors
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        invalid_fields(self.field_errors())
+    }
+
+    /// The trimmed address; every part is unset when none was given.
+    fn address(&self) -> Address {
+        self.address
+            .as_ref()
+            .map(Address::normalized)
+            .unwrap_or_default()
+    }
+}
+
+impl UpdateContact {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = FieldErrors::new();
+        if let Some(first_name) = &self.first_name {
+            check_name(&mut errors, "first_name", first_name);
+        }
+        if let Some(last_name) = &self.last_name {
+            check_name(&mut errors, "last_name", last_name);
+        }
+        if let Some(phone) = &self.phone {
+            check_phone(&mut errors, phone);
+        }
+        if let Some(phones) = &self.phones {
+            check_phones(&mut errors, phones);
+        }
+        if let Some(tags) = &self.tags {
+            check_tags(&mut errors, tags);
+        }
+        if let Some(Some(email)) = &self.email {
+            check_email(&mut errors, email);
+        }
+        if let Some(Some(birthday)) = self.birthday {
+            check_birthday(&mut errors, birthday);
+        }
+        if let Some(Some(notes)) = &self.notes {
+            check_notes(&mut errors, notes);
+        }
+        if let Some(Some(company)) = &self.company {
+            check_company(&mut errors, company);
+        }
+        if let Some(Some(country_code)) = &self.country_code {
+            check_country_code(&mut errors, country_code);
+        }
+        if let Some(Some(avatar_url)) = &self.avatar_url {
+            check_avatar_url(&mut errors, avatar_url);
+        }
+        if let Some(Some(address)) = &self.address {
+            check_address(&mut errors, address);
+        }
+        invalid_fields(errors)
+    }
+}
+
+/// Entry point of the `phone_book` binary: parses the command line and runs
+/// the chosen subcommand.
+pub async fn run() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    dotenvy::dotenv().ok();
+
+    let config = Config::from_env()?;
+    let logs = tracing_subscriber::registry().with(
+        EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("phone_book=info,tower_http=info,sqlx=warn")),
+    );
+    match config.log_format() {
+        LogFormat::Json => logs.with(fmt::layer().json()).init(),
+        LogFormat::Pretty => logs.with(fmt::layer()).init(),
+    }
+
+    match cli.command.unwrap_or_default() {
+        Command::Serve => serve(config).await,
+        Command::Migrate => {
+            let config = Config {
+                auto_migrate: true,
+                ..config
+            };
+            let db_pool = connect(&config).await?;
+            apply_strict_dedup(&db_pool, config.strict_dedup).await?;
+            db_pool.close().await;
+            tracing::info!("migrations are up to date");
+            Ok(())
+        }
+        Command::Seed => cli::seed(&connect(&config).await?).await,
+        Command::Export { out } => cli::export_csv(&connect(&config).await?, &out).await,
+    }
+}
+
+/// Runs the HTTP server until Ctrl-C or SIGTERM.
+async fn serve(config: Config) -> anyhow::Result<()> {
+    let db_pool = connect(&config).await?;
+    apply_strict_dedup(&db_pool, config.strict_dedup).await?;
+    idempotency::spawn_sweeper(db_pool.clone());
+
+    if config.read_only {
+        tracing::warn!("READ_ONLY is set; changes to contacts are refused until it is cleared");
+    }
+    let bind_addr = config.bind_addr;
+    let state = app_state(config, db_pool.clone())?;
+    let events = state.events.clone();
+    let usage = state.auth.usage().clone();
+    usage.spawn_flusher(db_pool.clone());
+    let app = router(state)?;
+
+    let listener = tokio::net::TcpListener::bind(bind_addr).await?;
+    tracing::info!("Listening on {}", listener.local_addr()?);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(events))
+    .await?;
+
+    tracing::info!("Shutting down, closing database pool");
+    if let Err(e) = usage.flush(&db_pool).await {
+        tracing::warn!(error = %e, "failed to record API usage");
+    }
+    db_pool.close().await;
+    
+    Ok(())
+}
+
+/// The application for `config`, on a pool that [`connect`] has set up.
+/// Serve it with `into_make_service_with_connect_info::<SocketAddr>()`; the
+/// rate limiter keys on the peer address.
+pub fn app(config: Config, db_pool: Pool<Postgres>) -> anyhow::Result<Router> {
+    router(app_state(config, db_pool)?)
+}
+
+fn app_state(config: Config, db_pool: Pool<Postgres>) -> anyhow::Result<AppState> {
+    let jwt = config
+        .jwt_secret
+        .as_ref()
+        .map(|secret| JwtKeys::new(secret.as_bytes(), Duration::from_secs(config.jwt_ttl_secs)));
+    let auth = AuthConfig::new(config.api_key.clone(), jwt, config.api_key_protect_reads);
+    if !auth.is_enabled() {
+        tracing::warn!(
+            "neither API_KEY nor JWT_SECRET is set; the API is open to anyone who can reach it"
+        );
+    }
+
+    let events = Events::new();
+    if let Some(url) = &config.webhook_url {
+        webhook::spawn(url, &events)
+            .with_context(|| format!("WEBHOOK_URL `{url}` is not a valid URL"))?;
+    }
+
+    let list_cache = Cache::new(config.list_cache_ttl());
+    let redis = match &config.redis_url {
+        Some(url) => {
+            let redis = RedisCache::new(url, config.redis_cache_ttl())
+                .context("REDIS_URL is not a valid Redis URL")?;
+            // Writes handled by other instances make local pages stale too.
+            let list_cache = list_cache.clone();
+            redis.spawn_subscriber(move || list_cache.invalidate());
+            Some(redis)
+        }
+        None => None,
+    };
+
+    Ok(AppState {
+        db_pool,
+        auth,
+        metrics: telemetry::install()?,
+        events,
+        list_cache,
+        redis,
+        config: Arc::new(config),
+    })
+}
+
+/// Builds the full application: every route with its middleware stack.
+fn router(state: AppState) -> anyhow::Result<Router> {
+    let config = Arc::clone(&state.config);
+    let cors = cors_layer(
+        config.cors_allowed_origins.as_deref(),
+        config.cors_permissive,
+    )?;
+
+    let mut contacts = Router::new()
+        .route(
+            "/contacts",
+            get(list_contacts)
+                .head(head_contacts)
+                .post(create_contact)
+                .patch(update_contacts)
+                .delete(delete_contacts),
+        )
+        .route(
+            "/contacts/:id",
+            get(get_contact)
+                .head(head_contact)
+                .put(replace_contact)
+                .patch(update_contact)
+                .delete(delete_contact),
+        )
+        .route("/contacts/:id/restore", post(restore_contact))
+        .route("/contacts/trash", get(list_trash))
+        .route("/contacts/by-phone/:phone", put(upsert_contact_by_phone))
+        .route("/contacts/trash/purge", delete(purge_trash))
+        .route("/contacts/:id/history", get(contact_history))
+        .route("/contacts/:id/avatar", get(contact_avatar))
+        .route("/contacts/:id/vcard", get(contact_vcard))
+        .route("/contacts/:id/qr", get(contact_qr))
+        .route("/contacts/events", get(contact_events))
+        .route(
+            "/contacts/:id/favorite",
+            post(favorite_contact).delete(unfavorite_contact),
+        )
+        .route("/contacts/:id/touch", post(touch_contact))
+        .route("/contacts/stale", get(stale_contacts))
+        .route("/contacts/batch", post(create_contacts_batch))
+        .route("/contacts/export.csv", get(export_contacts_csv))
+        .route("/contacts/export.vcf", get(export_contacts_vcf))
+        .route("/contacts/export.json", get(export_contacts_json))
+        .route("/contacts/search", get(search_contacts))
+        .route("/contacts/fuzzy", get(fuzzy_search_contacts))
+        .route("/contacts/suggest", get(suggest_contacts))
+        .route("/contacts/validate-phone", get(validate_phone))
+        .route("/contacts/stats", get(contact_stats))
+        .route("/contacts/index", get(contact_index))
+        .route("/contacts/random", get(random_contacts))
+        .route("/contacts/duplicates", get(find_duplicates))
+        .route("/contacts/merge", post(merge_contacts))
+        .route("/contacts/birthdays/upcoming", get(upcoming_birthdays))
+        .route("/contacts/companies", get(company_counts))
+        .layer((
+            RequestBodyLimitLayer::new(config.max_body_bytes),
+            TimeoutLayer::new(config.request_timeout()),
+        ))
+        // Imports get their own, larger body limit and timeout.
+        .merge(
+            Router::new()
+                .route("/contacts/import", post(import_contacts_csv))
+                .route("/contacts/import.json", post(import_contacts_json))
+                .layer((
+                    RequestBodyLimitLayer::new(config.max_import_body_bytes),
+                    TimeoutLayer::new(config.import_timeout()),
+                )),
+        );
+    if state.auth.is_enabled() {
+        contacts = contacts.route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_auth,
+        ));
+    }
+    contacts = contacts.route_layer(middleware::from_fn_with_state(
+        state.clone(),
+        invalidate_caches,
+    ));
+    if config.read_only {
+        contacts = contacts.route_layer(middleware::from_fn(reject_writes));
+    }
+
+    let mut api = Router::new()
+        .route("/auth/login", post(login))
+        .route("/auth/me", get(current_user))
+        .layer((
+            RequestBodyLimitLayer::new(config.max_body_bytes),
+            TimeoutLayer::new(config.request_timeout()),
+        ))
+        .merge(contacts)
+        .merge(
+            Router::new()
+                .route("/admin/migrations", get(applied_migrations))
+                .route("/admin/usage", get(api_usage))
+                .route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth::require_api_key,
+                )),
+        );
+    // Health probes are left out so a busy orchestrator can't throttle itself.
+    if config.rate_limit_per_minute > 0 {
+        api = api.route_layer(rate_limit::layer(
+            config.rate_limit_per_minute,
+            config.trusted_proxies.as_deref(),
+        )?);
+    }
+
+    let mut app = Router::new()
+        .route("/health", get(health))
+        .route("/livez", get(livez))
+        .route("/readyz", get(health))
+        .route("/metrics", get(metrics))
+        .merge(
+            SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", openapi::ApiDoc::openapi()),
+        )
+        .nest(API_PREFIX, api.clone())
+        // Unversioned paths keep working during the transition to `/v1`.
+        .merge(api.layer(middleware::from_fn(deprecate_unversioned)))
+        // Must come after every route, which it is only applied to.
+        .method_not_allowed_fallback(method_not_allowed)
+        .fallback(not_found)
+        .route_layer(middleware::from_fn(telemetry::track))
+        // Bodies are capped by the per-route `RequestBodyLimitLayer`s instead
+        // of axum's fixed 2 MB default.
+        .layer(DefaultBodyLimit::disable())
+        // The default predicate leaves tiny bodies, images, event streams, and
+        // anything that already has a `Content-Encoding` untouched.
+        .layer(CompressionLayer::new())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(make_request_span)
+                .on_response(
+                    DefaultOnResponse::new()
+                        .level(Level::INFO)
+                        .latency_unit(LatencyUnit::Millis),
+                ),
+        )
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .with_state(state);
+    if let Some(cors) = cors {
+        app = app.layer(cors);
+    }
+
+    Ok(app)
+}
+
+/// Answers paths no route matches, in the same JSON shape as other errors.
+async fn not_found() -> AppError {
+    AppError::NotFound("route not found".to_string())
+}
+
+/// Answers a known path called with the wrong method. The `Allow` header
+/// listing the supported ones is added by axum.
+async fn method_not_allowed(method: Method, OriginalUri(uri): OriginalUri) -> AppError {
+    AppError::MethodNotAllowed(format!("{method} is not allowed on {}", uri.path()))
+}
+
+const DB_CONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const DB_CONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Sets up the connection pool shared by every subcommand, migrating the
+/// database first unless `AUTO_MIGRATE` is off.
+pub async fn connect(config: &Config) -> anyhow::Result<Pool<Postgres>> {
+    tracing::info!(
+        max_connections = config.db_max_connections,
+        min_connections = config.db_min_connections,
+        acquire_timeout_secs = config.db_acquire_timeout_secs,
+        idle_timeout_secs = config.db_idle_timeout_secs,
+        slow_query_ms = config.slow_query_ms,
+        "configuring database pool"
+    );
+
+    // Statements slower than the threshold are logged by sqlx at `warn`,
+    // inside the request span, so the log line names the route.
+    let connect_options = PgConnectOptions::from_str(&config.database_url)
+        .context("invalid DATABASE_URL")?
+        .log_slow_statements(
+            if config.slow_query_ms > 0 {
+                LevelFilter::Warn
+            } else {
+                LevelFilter::Off
+            },
+            Duration::from_millis(config.slow_query_ms),
+        );
+
+    let pool_options = PgPoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .acquire_timeout(config.acquire_timeout())
+        .idle_timeout(config.idle_timeout());
+    let pool = connect_with_retry(
+        pool_options,
+        connect_options,
+        config.db_connect_attempts,
+        config.auto_migrate,
+    )
+    .await?;
+    if !config.auto_migrate {
+        let pending = pending_migrations(&mut *pool.acquire().await?).await?;
+        if !pending.is_empty() {
+            tracing::warn!("{}", pending_migrations_message(&pending));
+        }
+    }
+    Ok(pool)
+}
+
+/// Every migration bundled into the binary.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Versions of bundled migrations the database hasn't applied yet.
+async fn pending_migrations(conn: &mut PgConnection) -> Result<Vec<i64>, sqlx::Error> {
+    let tracked: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(&mut *conn)
+        .await?;
+    let applied: Vec<i64> = if tracked {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
+}
+
+fn pending_migrations_message(pending: &[i64]) -> String {
+    let versions: Vec<String> = pending.iter().map(i64::to_string).collect();
+    format!(
+        "the database schema is behind: migrations {} are pending; run `phone_book migrate`",
+        versions.join(", ")
+    )
+}
+
+/// Creates or drops the `STRICT_DEDUP` index to match the configuration. It
+/// is managed here rather than by a migration because it is optional, and
+/// only by `serve` and `migrate`, so one-off commands like `export` leave
+/// it alone.
+async fn apply_strict_dedup(pool: &Pool<Postgres>, enabled: bool) -> anyhow::Result<()> {
+    let statement = if enabled {
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS contacts_person_key
+        ON contacts (lower(first_name), lower(last_name), phone)
+        WHERE deleted_at IS NULL
+        "#
+    } else {
+        "DROP INDEX IF EXISTS contacts_person_key"
+    };
+    sqlx::query(statement)
+        .execute(pool)
+        .await
+        .context("failed to apply STRICT_DEDUP; are there duplicate contacts?")?;
+    Ok(())
+}
+
+/// Connects to the database and, with `migrate`, runs migrations, retrying
+/// with exponential backoff so the server can be started before Postgres
+/// accepts connections.
+async fn connect_with_retry(
+    options: PgPoolOptions,
+    connect_options: PgConnectOptions,
+    max_attempts: u32,
+    migrate: bool,
+) -> anyhow::Result<Pool<Postgres>> {
+    let mut backoff = DB_CONNECT_INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match connect_and_migrate(options.clone(), connect_options.clone(), migrate).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if attempt < max_attempts => {
+                tracing::warn!(
+                    attempt,
+                    max_attempts,
+                    retry_in_ms = backoff.as_millis() as u64,
+                    error = %format_args!("{e:#}"),
+                    "database is not ready, retrying"
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(DB_CONNECT_MAX_BACKOFF);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e.context(format!(
+                    "could not set up the database after {max_attempts} attempts"
+                )))
+            }
+        }
+    }
+}
+
+async fn connect_and_migrate(
+    options: PgPoolOptions,
+    connect_options: PgConnectOptions,
+    migrate: bool,
+) -> anyhow::Result<Pool<Postgres>> {
+    let pool = options
+        .connect_with(connect_options)
+        .await
+        .context("failed to connect to the database")?;
+    if migrate {
+        MIGRATOR
+            .run(&pool)
+            .await
+            .context("failed to run migrations")?;
+    }
+    Ok(pool)
+}
+
+/// Builds the CORS policy from `CORS_ALLOWED_ORIGINS`, a comma-separated list
+/// of origins. Without it, cross-origin requests are refused unless
+/// `CORS_PERMISSIVE` explicitly opts into allowing any origin.
+fn cors_layer(
+    allowed_origins: Option<&str>,
+    permissive: bool,
+) -> anyhow::Result<Option<CorsLayer>> {
+    let Some(allowed_origins) = allowed_origins.filter(|origins| !origins.trim().is_empty()) else {
+        if permissive {
+            tracing::warn!("CORS_PERMISSIVE is set; any origin may call the API");
+            return Ok(Some(CorsLayer::permissive()));
+        }
+        return Ok(None);
+    };
+
+    let origins = allowed_origins
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            HeaderValue::from_str(origin).with_context(|| {
+                format!("CORS_ALLOWED_ORIGINS entry `{origin}` is not a valid origin")
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    tracing::info!(origins = allowed_origins, "allowing cross-origin requests");
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ])
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                header::IF_MATCH,
+                header::IF_NONE_MATCH,
+                idempotency::HEADER,
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static("x-request-id"),
+            ])
+            .expose_headers([
+                DEPRECATION,
+                header::ETAG,
+                header::LINK,
+                header::LOCATION,
+                header::RETRY_AFTER,
+                HeaderName::from_static("x-request-id"),
+            ])
+            .allow_credentials(true),
+    ))
+}
+
+/// Builds the per-request tracing span, tagged with the id assigned by
+/// `SetRequestIdLayer` (or supplied by the client) in `x-request-id`.
+fn make_request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str);
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        route,
+        request_id = %request_id,
+    )
+}
+
+/// Marks responses to pre-`/v1` paths as deprecated and links to the
+/// versioned equivalent.
+async fn deprecate_unversioned(request: Request<Body>, next: middleware::Next) -> Response {
+    let successor = format!(
+        "<{API_PREFIX}{}>; rel=\"successor-version\"",
+        request.uri().path()
+    );
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+/// Turns away every request that could change contacts while `READ_ONLY`
+/// is set.
+async fn reject_writes(
+    request: Request<Body>,
+    next: middleware::Next,
+) -> Result<Response, AppError> {
+    if !request.method().is_safe() {
+        return Err(AppError::Unavailable {
+            message: "the phone book is in read-only mode".to_string(),
+            retry_after_secs: READ_ONLY_RETRY_AFTER_SECS,
+        });
+    }
+    Ok(next.run(request).await)
+}
+
+/// Empties the list cache, and the Redis cache of every instance, once any
+/// request that may have changed contacts has been handled.
+async fn invalidate_caches(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: middleware::Next,
+) -> Response {
+    let is_write = !request.method().is_safe();
+    let response = next.run(request).await;
+    if is_write {
+        state.list_cache.invalidate();
+        if let Some(redis) = &state.redis {
+            redis.invalidate().await;
+        }
+    }
+    response
+}
+
+/// Resolves on Ctrl-C or SIGTERM, after closing open event streams so they
+/// don't keep the server running.
+async fn shutdown_signal(events: Events) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    events.close();
+}
+
+/// Prometheus scrape endpoint.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, description = "Prometheus text format", body = String, content_type = "text/plain"))
+)]
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    telemetry::record_pool_stats(&state.db_pool);
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, ToSchema)]
+struct HealthStatus {
+    status: &'static str,
+    /// Why the service isn't ready, when it can tell, e.g. pending
+    /// migrations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    /// Connection pool state; only reported by readiness checks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    db: Option<PoolHealth>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct PoolHealth {
+    /// Open connections, idle or in use.
+    size: u32,
+    idle: usize,
+    /// How long getting a connection from the pool took; `null` when none
+    /// could be acquired in time.
+    acquire_latency_ms: Option<u64>,
+}
+
+/// Liveness only reports that the process is serving requests; it never
+/// touches the database so a Postgres outage doesn't get the pod restarted.
+#[utoipa::path(
+    get,
+    path = "/livez",
+    tag = "health",
+    responses((status = 200, body = HealthStatus))
+)]
+async fn livez() -> Json<HealthStatus> {
+    Json(HealthStatus {
+        status: "ok",
+        reason: None,
+        db: None,
+    })
+}
+
+/// Readiness: the service is only ready when it can reach Postgres. The pool
+/// figures tell a saturated pool (no idle connections, slow acquires) apart
+/// from an unreachable database.
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "The database is reachable", body = HealthStatus),
+        (status = 503, description = "The database is unreachable", body = HealthStatus)
+    )
+)]
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthStatus>) {
+    let pool = &state.db_pool;
+    let (size, idle) = (pool.size(), pool.num_idle());
+
+    let check = async {
+        let started = Instant::now();
+        let mut conn = pool.acquire().await?;
+        let acquire_latency = started.elapsed();
+        sqlx::query("SELECT 1").execute(&mut *conn).await?;
+        // Migrating at startup already guarantees an up-to-date schema.
+        let pending = if state.config.auto_migrate {
+            Vec::new()
+        } else {
+            pending_migrations(&mut conn).await?
+        };
+        Ok::<_, sqlx::Error>((acquire_latency, pending))
+    };
+    let (status_code, status, reason, acquire_latency) =
+        match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check).await {
+            Ok(Ok((latency, pending))) if pending.is_empty() => {
+                (StatusCode::OK, "ok", None, Some(latency))
+            }
+            Ok(Ok((latency, pending))) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "degraded",
+                Some(pending_migrations_message(&pending)),
+                Some(latency),
+            ),
+            _ => (StatusCode::SERVICE_UNAVAILABLE, "degraded", None, None),
+        };
+
+    let db = PoolHealth {
+        size,
+        idle,
+        acquire_latency_ms: acquire_latency.map(|latency| latency.as_millis() as u64),
+    };
+    (
+        status_code,
+        Json(HealthStatus {
+            status,
+            reason,
+            db: Some(db),
+        }),
+    )
+}
+
+#[derive(Deserialize, ToSchema)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct TokenResponse {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: u64,
+}
+
+/// Checked in place of a real hash when the username is unknown. It uses the
+/// default Argon2 parameters, like the hashes users are created with.
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$unRjnYz0TLfcVtISQYb18A$O1UCLnSw8ETgs4W1HYwd6udC0yQ9CXe9fr/vDuYE+xc";
+
+/// Exchanges a username and password for a signed access token.
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = TokenResponse),
+        (status = 401, description = "Wrong username or password", body = ErrorBody),
+        (status = 404, description = "`JWT_SECRET` is not configured", body = ErrorBody)
+    )
+)]
+async fn login(
+    State(state): State<AppState>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    let jwt = state.auth.jwt().ok_or_else(|| {
+        AppError::NotFound("token authentication is not enabled".to_string())
+    })?;
+    let invalid = || AppError::Unauthorized("invalid username or password".to_string());
+
+    let user = sqlx::query_as::<_, (Uuid, String, String)>(
+        "SELECT id, username, password_hash FROM users WHERE username = $1"
+    )
+    .bind(payload.username.trim())
+    .fetch_optional(&state.db_pool)
+    .await?;
+
+    // An unknown username still pays for a hash check, so it isn't answered
+    // faster than a wrong password.
+    let password_hash = match &user {
+        Some((_, _, password_hash)) => password_hash.clone(),
+        None => DUMMY_PASSWORD_HASH.to_string(),
+    };
+    // Argon2 is deliberately slow, so keep it off the async worker threads.
+    let verified = tokio::task::spawn_blocking(move || {
+        auth::verify_password(&payload.password, &password_hash)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    let Some((id, username, _)) = user.filter(|_| verified) else {
+        return Err(invalid());
+    };
+
+    Ok(Json(TokenResponse {
+        access_token: jwt.issue(&AuthUser { id, username })?,
+        token_type: "Bearer",
+        expires_in: jwt.ttl().as_secs(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = AuthUser),
+        (status = 401, description = "Missing, invalid, or expired token", body = ErrorBody)
+    )
+)]
+async fn current_user(user: AuthUser) -> Json<AuthUser> {
+    Json(user)
+}
+
+/// A migration recorded in sqlx's `_sqlx_migrations` table.
+#[derive(Serialize, FromRow, ToSchema)]
+struct AppliedMigration {
+    /// Numeric prefix of the migration file, e.g. `18` for
+    /// `0018_add_avatar_url.sql`.
+    version: i64,
+    description: String,
+    installed_on: DateTime<Utc>,
+    /// False for a migration that failed partway and needs fixing by hand.
+    success: bool,
+}
+
+/// Lists the migrations applied to the database, oldest first.
+#[utoipa::path(
+    get,
+    path = "/admin/migrations",
+    tag = "admin",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = [AppliedMigration]),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody)
+    )
+)]
+async fn applied_migrations(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AppliedMigration>>, AppError> {
+    let migrations = sqlx::query_as::<_, AppliedMigration>(
+        "SELECT version, description, installed_on, success FROM _sqlx_migrations ORDER BY version"
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(migrations))
+}
+
+#[derive(Serialize, ToSchema)]
+struct UsageReport {
+    /// First day of the current month (UTC), which the counts cover.
+    period: NaiveDate,
+    /// Busiest first.
+    clients: Vec<ClientUsage>,
+}
+
+#[derive(Serialize, FromRow, ToSchema)]
+struct ClientUsage {
+    /// `api_key:` and a fingerprint of the key, or `user:` and the name of a
+    /// user with a login token.
+    client: String,
+    requests: i64,
+}
+
+/// Counts this month's authenticated requests per API key and user, across
+/// every instance.
+#[utoipa::path(
+    get,
+    path = "/admin/usage",
+    tag = "admin",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = UsageReport),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody)
+    )
+)]
+async fn api_usage(State(state): State<AppState>) -> Result<Json<UsageReport>, AppError> {
+    // Other instances' latest counts only arrive with their next flush.
+    state.auth.usage().flush(&state.db_pool).await?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let period: NaiveDate =
+        sqlx::query_scalar("SELECT date_trunc('month', NOW() AT TIME ZONE 'UTC')::DATE")
+            .fetch_one(&mut *tx)
+            .await?;
+    let clients = sqlx::query_as::<_, ClientUsage>(
+        "SELECT client, requests FROM api_usage WHERE period = $1 ORDER BY requests DESC, client"
+    )
+    .bind(period)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(UsageReport { period, clients }))
+}
+
+const DEFAULT_PAGE_LIMIT: i64 = 50;
+const MAX_PAGE_LIMIT: i64 = 200;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ListFilter {
+    #[serde(default)]
+    include_deleted: bool,
+    /// RFC 3339 timestamp, e.g. `2024-01-31T00:00:00Z`.
+    created_after: Option<String>,
+    /// RFC 3339 timestamp.
+    created_before: Option<String>,
+    /// RFC 3339 timestamp.
+    updated_after: Option<String>,
+    /// RFC 3339 timestamp.
+    updated_before: Option<String>,
+    /// Only contacts carrying this tag; repeat to require several.
+    #[serde(default)]
+    tag: Vec<String>,
+    /// `true` for starred contacts only, `false` for the rest.
+    favorites: Option<bool>,
+    /// Exact company name, ignoring case.
+    company: Option<String>,
+}
+
+impl ListFilter {
+    /// Appends the `WHERE` clause. Every given bound and tag is `AND`ed in as
+    /// a bound parameter; bounds are exclusive.
+    fn push_where(&self, query: &mut QueryBuilder<'_, Postgres>) -> Result<(), AppError> {
+        query.push(if self.include_deleted {
+            " WHERE TRUE"
+        } else {
+            " WHERE deleted_at IS NULL"
+        });
+
+        let bounds = [
+            ("created_after", "created_at >", &self.created_after),
+            ("created_before", "created_at <", &self.created_before),
+            ("updated_after", "updated_at >", &self.updated_after),
+            ("updated_before", "updated_at <", &self.updated_before),
+        ];
+        for (param, condition, value) in bounds {
+            if let Some(value) = value {
+                query
+                    .push(format_args!(" AND {condition} "))
+                    .push_bind(parse_timestamp(param, value)?);
+            }
+        }
+
+        if let Some(favorites) = self.favorites {
+            query.push(" AND is_favorite = ").push_bind(favorites);
+        }
+        if let Some(company) = &self.company {
+            query
+                .push(" AND lower(company) = lower(")
+                .push_bind(company.trim().to_string())
+                .push(")");
+        }
+
+        for tag in normalize_tags(&self.tag) {
+            query
+                .push(
+                    " AND EXISTS (SELECT 1 FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id \
+                     WHERE ct.contact_id = contacts.id AND t.name = ",
+                )
+                .push_bind(tag)
+                .push(")");
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_timestamp(param: &str, value: &str) -> Result<DateTime<Utc>, AppError> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|_| {
+            AppError::BadRequest(format!(
+                "`{param}` must be an RFC 3339 timestamp such as 2024-01-31T00:00:00Z"
+            ))
+        })
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct Pagination {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    /// `next_cursor` of the previous page; continues after it instead of
+    /// skipping `offset` rows. Not combinable with `offset`.
+    after: Option<String>,
+}
+
+impl Pagination {
+    /// Resolves the requested page into `(limit, offset)`, applying defaults
+    /// and capping the limit at `MAX_PAGE_LIMIT`.
+    fn resolve(&self) -> Result<(i64, i64), AppError> {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        let offset = self.offset.unwrap_or(0);
+
+        if limit < 0 || offset < 0 {
+            return Err(AppError::BadRequest(
+                "limit and offset must not be negative".to_string(),
+            ));
+        }
+
+        Ok((limit.min(MAX_PAGE_LIMIT), offset))
+    }
+
+    /// Decodes `after`, if given.
+    fn cursor(&self, sorting: &Sorting) -> Result<Option<cursor::Cursor>, AppError> {
+        let Some(after) = &self.after else {
+            return Ok(None);
+        };
+        if self.offset.is_some() {
+            return Err(AppError::BadRequest(
+                "`after` and `offset` cannot be combined".to_string(),
+            ));
+        }
+        if !sorting.is_name_order() {
+            return Err(AppError::BadRequest(
+                "`after` only works with the default name order".to_string(),
+            ));
+        }
+        cursor::decode(after).map(Some)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct Sorting {
+    /// Defaults to `DEFAULT_SORT`.
+    #[param(inline)]
+    sort_by: Option<SortField>,
+    #[serde(default)]
+    #[param(inline)]
+    order: SortOrder,
+    /// List starred contacts before all others.
+    #[serde(default)]
+    favorites_first: bool,
+}
+
+impl Sorting {
+    /// Fills in `sort_by` from `DEFAULT_SORT` when the request has none.
+    fn or_default(self, default: SortField) -> Self {
+        Self {
+            sort_by: Some(self.sort_by.unwrap_or(default)),
+            ..self
+        }
+    }
+
+    /// Builds the `ORDER BY` clause. Only whitelisted column names ever reach
+    /// the SQL string. `id` always comes last to break ties, so rows sharing
+    /// a name or timestamp keep their place between pages and cursors always
+    /// point at exactly one row.
+    fn order_by(&self) -> String {
+        let direction = match self.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+
+        let columns = match self.sort_by {
+            None | Some(SortField::FirstName) => {
+                format!("first_name {direction}, last_name {direction}, id {direction}")
+            }
+            Some(SortField::LastName) => {
+                format!("last_name {direction}, first_name {direction}, id {direction}")
+            }
+            Some(SortField::CreatedAt) => format!("created_at {direction}, id {direction}"),
+            Some(SortField::UpdatedAt) => format!("updated_at {direction}, id {direction}"),
+        };
+        if self.favorites_first {
+            format!("is_favorite DESC, {columns}")
+        } else {
+            columns
+        }
+    }
+
+    /// Whether rows are ordered by `(first_name, last_name, id)` alone, the
+    /// only order cursors support.
+    fn is_name_order(&self) -> bool {
+        matches!(self.sort_by, None | Some(SortField::FirstName)) && !self.favorites_first
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+struct PaginatedContacts {
+    items: Vec<Contact>,
+    total: i64,
+    limit: i64,
+    offset: i64,
+    /// Pass as `after` to fetch the next page; `null` on the last page, and
+    /// always `null` unless contacts are in the default name order.
+    next_cursor: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/contacts",
+    tag = "contacts",
+    params(Pagination, Sorting, ListFilter, FieldSelection),
+    responses(
+        (status = 200, body = PaginatedContacts, content_type = ["application/json", "application/xml"]),
+        (status = 400, description = "Invalid query parameters", body = ErrorBody),
+        (status = 406, description = "`Accept` allows neither JSON nor XML", body = ErrorBody)
+    )
+)]
+async fn list_contacts(
+    State(state): State<AppState>,
+    Query(pagination): Query<Pagination>,
+    Query(sorting): Query<Sorting>,
+    MultiQuery(filter): MultiQuery<ListFilter>,
+    Query(selection): Query<FieldSelection>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let format = Format::from_headers(&headers)?;
+    let sorting = sorting.or_default(state.config.default_sort);
+    let (limit, offset) = pagination.resolve()?;
+    let cursor = pagination.cursor(&sorting)?;
+    let fields = selection.parse()?;
+
+    // Every parameter is part of the key, though only some shape the page.
+    let cache_key = query.unwrap_or_default();
+    let (page, cache_status) = match state.list_cache.get(&cache_key) {
+        Some(page) => (page, "HIT"),
+        None => {
+            let generation = state.list_cache.generation();
+            let page =
+                fetch_contacts_page(&state.db_pool, &filter, &sorting, limit, offset, cursor)
+                    .await?;
+            let page = Arc::new(page);
+            state
+                .list_cache
+                .insert(cache_key, generation, Arc::clone(&page));
+            (page, "MISS")
+        }
+    };
+    // Cached pages stay E.164, so only formatted ones are copied.
+    let page = match selection.phone_format {
+        PhoneFormat::E164 => page,
+        phone_format => {
+            let mut page = PaginatedContacts::clone(&page);
+            for contact in &mut page.items {
+                contact.format_phones(phone_format);
+            }
+            Arc::new(page)
+        }
+    };
+
+    let mut response = match fields {
+        Some(fields) => format.render("contacts", fields::select(&*page, &fields, Some("items"))?),
+        None => format.render("contacts", &*page),
+    }?;
+    if state.list_cache.is_enabled() {
+        response
+            .headers_mut()
+            .insert(X_CACHE, HeaderValue::from_static(cache_status));
+    }
+    Ok(response)
+}
+
+/// `GET /contacts` without the body, for checking a page's headers.
+#[utoipa::path(
+    head,
+    path = "/contacts",
+    tag = "contacts",
+    params(Pagination, Sorting, ListFilter, FieldSelection),
+    responses(
+        (status = 200, description = "The headers `GET` would send, with its `Content-Length`"),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 406, description = "`Accept` allows neither JSON nor XML")
+    )
+)]
+async fn head_contacts(
+    state: State<AppState>,
+    pagination: Query<Pagination>,
+    sorting: Query<Sorting>,
+    filter: MultiQuery<ListFilter>,
+    selection: Query<FieldSelection>,
+    query: RawQuery,
+    headers: HeaderMap,
+) -> Response {
+    let response = list_contacts(
+        state, pagination, sorting, filter, selection, query, headers,
+    )
+    .await
+    .into_response();
+    without_body(response).await
+}
+
+/// One page of contacts, with the total across all pages.
+async fn fetch_contacts_page(
+    db_pool: &Pool<Postgres>,
+    filter: &ListFilter,
+    sorting: &Sorting,
+    limit: i64,
+    offset: i64,
+    cursor: Option<cursor::Cursor>,
+) -> Result<PaginatedContacts, AppError> {
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM contacts");
+    filter.push_where(&mut count)?;
+
+    let mut select = QueryBuilder::new("SELECT * FROM contacts");
+    filter.push_where(&mut select)?;
+    if let Some(cursor) = cursor {
+        let comparison = match sorting.order {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        };
+        select
+            .push(format_args!(
+                " AND (first_name, last_name, id) {comparison} ("
+            ))
+            .push_bind(cursor.first_name)
+            .push(", ")
+            .push_bind(cursor.last_name)
+            .push(", ")
+            .push_bind(cursor.id)
+            .push(")");
+    }
+    // One extra row tells whether there is a next page.
+    select
+        .push(format_args!(" ORDER BY {} LIMIT ", sorting.order_by()))
+        .push_bind(limit + 1)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let mut tx = db_pool.begin().await?;
+
+    // Both queries must see the same snapshot for `total` to match `items`.
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let total: i64 = count.build_query_scalar().fetch_one(&mut *tx).await?;
+
+    let mut items = select
+        .build_query_as::<Contact>()
+        .fetch_all(&mut *tx)
+        .await?;
+    let has_more = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+    load_related(&mut tx, &mut items).await?;
+
+    tx.commit().await?;
+
+    let next_cursor = if has_more && sorting.is_name_order() {
+        items.last().map(cursor::encode)
+    } else {
+        None
+    };
+
+    Ok(PaginatedContacts {
+        items,
+        total,
+        limit,
+        offset,
+        next_cursor,
+    })
+}
+
+const DEFAULT_PHONE_LABEL: &str = "mobile";
+
+/// Normalizes every number in a submitted phone list to E.164.
+fn normalize_phone_list(
+    phones: &[PhoneNumber],
+    region: Option<country::Id>,
+) -> Result<Vec<PhoneNumber>, AppError> {
+    phones
+        .iter()
+        .enumerate()
+        .map(|(index, phone)| {
+            let number = normalize_phone(&phone.number, region)
+                .map_err(|e| AppError::Validation(format!("phones[{index}]: {e}")))?;
+            Ok(PhoneNumber {
+                label: phone.label.trim().to_string(),
+                number,
+            })
+        })
+        .collect()
+}
+
+/// Ensures the primary number is part of the list, adding it first under
+/// the default label when it's missing.
+fn with_primary(mut phones: Vec<PhoneNumber>, primary: &str) -> Vec<PhoneNumber> {
+    if !phones.iter().any(|phone| phone.number == primary) {
+        phones.insert(
+            0,
+            PhoneNumber {
+                label: DEFAULT_PHONE_LABEL.to_string(),
+                number: primary.to_string(),
+            },
+        );
+    }
+    phones
+}
+
+/// Fills in `phones` and `tags` for each contact, with one query apiece.
+async fn load_related(
+    conn: &mut PgConnection,
+    contacts: &mut [Contact],
+) -> Result<(), sqlx::Error> {
+    if contacts.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<Uuid> = contacts.iter().map(|contact| contact.id).collect();
+    let rows = sqlx::query_as::<_, (Uuid, String, String)>(
+        r#"
+        SELECT contact_id, label, number FROM phone_numbers
+        WHERE contact_id = ANY($1)
+        ORDER BY contact_id, position
+        "#
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let tag_rows = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT ct.contact_id, t.name
+        FROM contact_tags ct
+        JOIN tags t ON t.id = ct.tag_id
+        WHERE ct.contact_id = ANY($1)
+        ORDER BY ct.contact_id, t.name
+        "#
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut phones: HashMap<Uuid, Vec<PhoneNumber>> = HashMap::new();
+    for (contact_id, label, number) in rows {
+        phones
+            .entry(contact_id)
+            .or_default()
+            .push(PhoneNumber { label, number });
+    }
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (contact_id, name) in tag_rows {
+        tags.entry(contact_id).or_default().push(name);
+    }
+    for contact in contacts {
+        contact.phones = phones.remove(&contact.id).unwrap_or_default();
+        contact.tags = tags.remove(&contact.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Trims and lower-cases tag names, sorted and without duplicates.
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags.iter().map(|tag| tag.trim().to_lowercase()).collect();
+    tags.sort_unstable();
+    tags.dedup();
+    tags
+}
+
+/// Replaces the tags of a contact, creating any that don't exist yet.
+async fn replace_tags(
+    conn: &mut PgConnection,
+    contact_id: Uuid,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM contact_tags WHERE contact_id = $1")
+        .bind(contact_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("INSERT INTO tags (name) SELECT UNNEST($1::text[]) ON CONFLICT (name) DO NOTHING")
+        .bind(tags)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO contact_tags (contact_id, tag_id)
+        SELECT $1, id FROM tags WHERE name = ANY($2)
+        "#
+    )
+    .bind(contact_id)
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Replaces all stored numbers of a contact, preserving list order.
+async fn replace_phones(
+    conn: &mut PgConnection,
+    contact_id: Uuid,
+    phones: &[PhoneNumber],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM phone_numbers WHERE contact_id = $1")
+        .bind(contact_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let labels: Vec<&str> = phones.iter().map(|phone| phone.label.as_str()).collect();
+    let numbers: Vec<&str> = phones.iter().map(|phone| phone.number.as_str()).collect();
+    sqlx::query(
+        r#"
+        INSERT INTO phone_numbers (contact_id, label, number, position)
+        SELECT $1, label, number, position - 1
+        FROM UNNEST($2::text[], $3::text[]) WITH ORDINALITY AS t(label, number, position)
+        "#
+    )
+    .bind(contact_id)
+    .bind(&labels)
+    .bind(&numbers)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Inserts a validated contact whose numbers have already been normalized.
+async fn insert_contact(
+    conn: &mut PgConnection,
+    actor: &Actor,
+    payload: &CreateContact,
+    phone: &str,
+    phones: &[PhoneNumber],
+) -> Result<Contact, sqlx::Error> {
+    let address = payload.address();
+    let mut contact = sqlx::query_as::<_, Contact>(
+        r#"
+        INSERT INTO contacts (
+            id, first_name, last_name, phone, email, birthday, notes, company, country_code,
+            avatar_url, street, city, state, postal_code, country, is_favorite
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        RETURNING *
+        "#
+    )
+    .bind(Uuid::new_v4())
+    .bind(payload.first_name.trim())
+    .bind(payload.last_name.trim())
+    .bind(phone)
+    .bind(payload.email.as_deref().map(str::trim))
+    .bind(payload.birthday)
+    .bind(payload.notes.as_deref().map(str::trim))
+    .bind(normalize_company(payload.company.as_deref()))
+    .bind(normalize_country_code(payload.country_code.as_deref()))
+    .bind(normalize_avatar_url(payload.avatar_url.as_deref()))
+    .bind(&address.street)
+    .bind(&address.city)
+    .bind(&address.state)
+    .bind(&address.postal_code)
+    .bind(&address.country)
+    .bind(payload.is_favorite.unwrap_or(false))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    replace_phones(&mut *conn, contact.id, phones).await?;
+    contact.phones = phones.to_vec();
+
+    let tags = normalize_tags(payload.tags.as_deref().unwrap_or_default());
+    replace_tags(&mut *conn, contact.id, &tags).await?;
+    contact.tags = tags;
+
+    audit::record(
+        conn,
+        actor,
+        contact.id,
+        Action::Create,
+        None,
+        Some(&contact),
+    )
+    .await?;
+
+    Ok(contact)
+}
+
+/// Normalizes the primary phone and the full phone list of a new contact.
+fn resolve_phones(payload: &CreateContact) -> Result<(String, Vec<PhoneNumber>), AppError> {
+    let region = payload
+        .country_code
+        .as_deref()
+        .and_then(phone::parse_region);
+    let phone = normalize_phone(&payload.phone, region)?;
+    let phones = normalize_phone_list(payload.phones.as_deref().unwrap_or_default(), region)?;
+    let phones = with_primary(phones, &phone);
+    Ok((phone, phones))
+}
+
+/// Like [`resolve_phones`], but records every number that isn't valid under
+/// its own field instead of stopping at the first.
+fn resolve_phone_fields(
+    payload: &CreateContact,
+    errors: &mut FieldErrors,
+) -> Option<(String, Vec<PhoneNumber>)> {
+    let region = payload
+        .country_code
+        .as_deref()
+        .and_then(phone::parse_region);
+    let phone = match normalize_phone(&payload.phone, region) {
+        Ok(phone) => Some(phone),
+        Err(e) => {
+            errors.insert("phone".to_string(), e.to_string());
+            None
+        }
+    };
+    let mut phones = Vec::new();
+    for (index, number) in payload.phones.iter().flatten().enumerate() {
+        match normalize_phone(&number.number, region) {
+            Ok(normalized) => phones.push(PhoneNumber {
+                label: number.label.trim().to_string(),
+                number: normalized,
+            }),
+            Err(e) => {
+                errors.insert(format!("phones[{index}].number"), e.to_string());
+            }
+        }
+    }
+
+    let phone = phone.filter(|_| errors.is_empty())?;
+    let phones = with_primary(phones, &phone);
+    Some((phone, phones))
+}
+
+/// With an `Idempotency-Key` header, replaying the same key within 24 hours
+/// returns the contact created the first time with `200 OK` instead of
+/// inserting another one.
+#[utoipa::path(
+    post,
+    path = "/contacts",
+    tag = "contacts",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key that makes retries safe")
+    ),
+    request_body = CreateContact,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Replayed `Idempotency-Key`; the original contact", body = Contact),
+        (status = 201, description = "Created; `Location` points at the new contact", body = Contact),
+        (status = 400, description = "Malformed `Idempotency-Key`", body = ErrorBody),
+        (status = 409, description = "The phone number is taken", body = ErrorBody),
+        (status = 422, description = "Invalid fields", body = ErrorBody)
+    )
+)]
+async fn create_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    headers: HeaderMap,
+    Json(payload): Json<CreateContact>,
+) -> Result<Response, AppError> {
+    payload.validate()?;
+    let idempotency_key = idempotency::key_from_headers(&headers)?;
+    let (phone, phones) = resolve_phones(&payload)?;
+
+    let mut tx = state.db_pool.begin().await?;
+
+    if let Some(key) = idempotency_key {
+        if let Some(id) = idempotency::lock_and_lookup(&mut tx, key).await? {
+            let mut contact = sqlx::query_as::<_, Contact>("SELECT * FROM contacts WHERE id = $1")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+            load_related(&mut tx, slice::from_mut(&mut contact)).await?;
+            tx.commit().await?;
+            return created_response(StatusCode::OK, contact);
+        }
+    }
+
+    let contact = insert_contact(&mut tx, &actor, &payload, &phone, &phones).await?;
+    if let Some(key) = idempotency_key {
+        idempotency::remember(&mut tx, key, contact.id).await?;
+    }
+    tx.commit().await?;
+    state.events.publish(Event::Created, &contact);
+
+    created_response(StatusCode::CREATED, contact)
+}
+
+/// Responds with the contact and a `Location` header pointing at it.
+fn created_response(status: StatusCode, contact: Contact) -> Result<Response, AppError> {
+    let location = HeaderValue::from_str(&format!("{API_PREFIX}/contacts/{}", contact.id))
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut response = (status, Json(contact)).into_response();
+    response.headers_mut().insert(header::LOCATION, location);
+
+    Ok(response)
+}
+
+/// Creates all contacts in one transaction. Validation errors are reported
+/// for every offending item (keyed as `[index].field`); a write failure such
+/// as a duplicate phone rolls back the whole batch and names the item.
+#[utoipa::path(
+    post,
+    path = "/contacts/batch",
+    tag = "contacts",
+    request_body = Vec<CreateContact>,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 201, body = Vec<Contact>),
+        (status = 409, description = "A phone number is taken; nothing was created", body = ErrorBody),
+        (status = 422, description = "Invalid fields, keyed as `[index].field`", body = ErrorBody)
+    )
+)]
+async fn create_contacts_batch(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<Vec<CreateContact>>,
+) -> Result<(StatusCode, Json<Vec<Contact>>), AppError> {
+    let mut errors = FieldErrors::new();
+    let mut phones = Vec::with_capacity(payload.len());
+
+    for (index, item) in payload.iter().enumerate() {
+        for (field, message) in item.field_errors() {
+            errors.insert(format!("[{index}].{field}"), message);
+        }
+        match resolve_phones(item) {
+            Ok(resolved) => phones.push(resolved),
+            Err(e) => {
+                errors
+                    .entry(format!("[{index}].phone"))
+                    .or_insert_with(|| e.to_string());
+            }
+        }
+    }
+    invalid_fields(errors)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let mut created = Vec::with_capacity(payload.len());
+
+    for (index, (item, (phone, numbers))) in payload.iter().zip(&phones).enumerate() {
+        match insert_contact(&mut tx, &actor, item, phone, numbers).await {
+            Ok(contact) => created.push(contact),
+            Err(e) => {
+                return Err(match AppError::from(e) {
+                    AppError::Conflict(message) => {
+                        AppError::Conflict(format!("contact at index {index}: {message}"))
+                    }
+                    other => other,
+                })
+            }
+        }
+    }
+
+    tx.commit().await?;
+    for contact in &created {
+        state.events.publish(Event::Created, contact);
+    }
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Serves both `GET /contacts/:id` and `GET /contacts/:id.vcf`. Axum path
+/// parameters always span a whole segment, so the extension is split off here.
+#[utoipa::path(
+    get,
+    path = "/contacts/{id}",
+    tag = "contacts",
+    params(
+        ("id" = Uuid, Path, description = "Contact id; append `.vcf` for a vCard"),
+        ("If-None-Match" = Option<String>, Header, description = "Return `304` if the ETag still matches"),
+        FieldSelection
+    ),
+    responses(
+        (status = 200, description = "The contact, with its `ETag`", body = Contact, content_type = ["application/json", "application/xml"]),
+        (status = 304, description = "Not modified"),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 406, description = "`Accept` allows neither JSON nor XML", body = ErrorBody)
+    )
+)]
+async fn get_contact(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(selection): Query<FieldSelection>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    match id.strip_suffix(".vcf") {
+        Some(id) => {
+            let contact = fetch_contact(&state, parse_contact_id(id)?).await?;
+            Ok(vcard_file(&contact))
+        }
+        None => {
+            let format = Format::from_headers(&headers)?;
+            let fields = selection.parse()?;
+            let id = parse_contact_id(&id)?;
+            let mut contact =
+                cached(&state, &format!("contact:{id}"), fetch_contact(&state, id)).await?;
+            contact.format_phones(selection.phone_format);
+            let etag =
+                representation_etag(&contact, format, fields.as_deref(), selection.phone_format);
+            if if_none_match(&headers, &etag) {
+                return Ok((
+                    StatusCode::NOT_MODIFIED,
+                    [(header::ETAG, etag), (header::VARY, "accept".to_string())],
+                )
+                    .into_response());
+            }
+            let body = match fields {
+                Some(fields) => {
+                    format.render("contact", fields::select(&contact, &fields, None)?)?
+                }
+                None => format.render("contact", contact)?,
+            };
+            Ok(([(header::ETAG, etag)], body).into_response())
+        }
+    }
+}
+
+/// `GET /contacts/:id` without the body, to check whether its `ETag` changed.
+#[utoipa::path(
+    head,
+    path = "/contacts/{id}",
+    tag = "contacts",
+    params(
+        ("id" = Uuid, Path, description = "Contact id; append `.vcf` for a vCard"),
+        ("If-None-Match" = Option<String>, Header, description = "Return `304` if the ETag still matches"),
+        FieldSelection
+    ),
+    responses(
+        (status = 200, description = "The headers `GET` would send, with the `ETag` and `Content-Length`"),
+        (status = 304, description = "Not modified"),
+        (status = 400, description = "Malformed id"),
+        (status = 404, description = "No such contact"),
+        (status = 406, description = "`Accept` allows neither JSON nor XML")
+    )
+)]
+async fn head_contact(
+    state: State<AppState>,
+    id: Path<String>,
+    selection: Query<FieldSelection>,
+    headers: HeaderMap,
+) -> Response {
+    let response = get_contact(state, id, selection, headers)
+        .await
+        .into_response();
+    without_body(response).await
+}
+
+/// Answers `HEAD` with the status and headers of the `GET` response,
+/// including the `Content-Length` of the body it leaves out.
+async fn without_body(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => {
+            parts
+                .headers
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+        }
+        Err(e) => tracing::warn!(error = %e, "failed to measure the body of a HEAD response"),
+    }
+    Response::from_parts(parts, Body::empty())
+}
+
+/// Strong entity tag for a contact; it changes whenever the version is bumped.
+fn contact_etag(contact: &Contact) -> String {
+    format!("\"{}\"", contact.version)
+}
+
+/// Entity tag of the representation `get_contact` serves. Whole JSON
+/// contacts with E.164 numbers keep the plain [`contact_etag`], which
+/// `If-Match` is checked against; XML, a `fields` subset, and other phone
+/// formats each extend it, since a strong tag must differ between
+/// representations.
+fn representation_etag(
+    contact: &Contact,
+    format: Format,
+    fields: Option<&[&str]>,
+    phone_format: PhoneFormat,
+) -> String {
+    let mut tag = contact.version.to_string();
+    if format == Format::Xml {
+        tag.push_str("-xml");
+    }
+    if let Some(fields) = fields {
+        // The body lists fields in a fixed order whatever the request's.
+        let mut fields = fields.to_vec();
+        fields.sort_unstable();
+        fields.dedup();
+        tag.push_str("-fields=");
+        tag.push_str(&fields.join("+"));
+    }
+    match phone_format {
+        PhoneFormat::E164 => {}
+        PhoneFormat::International => tag.push_str("-international"),
+        PhoneFormat::National => tag.push_str("-national"),
+    }
+    format!("\"{tag}\"")
+}
+
+fn with_etag(contact: Contact) -> Response {
+    ([(header::ETAG, contact_etag(&contact))], Json(contact)).into_response()
+}
+
+/// Whether the `If-None-Match` header matches `etag`.
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| etag_matches(value, etag))
+}
+
+/// Enforces an `If-Match` precondition, if the request carries one.
+fn check_if_match(headers: &HeaderMap, contact: &Contact) -> Result<(), AppError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(());
+    };
+    let etag = contact_etag(contact);
+    if etag_matches(value, &etag) {
+        Ok(())
+    } else {
+        Err(AppError::PreconditionFailed(format!(
+            "If-Match does not match the current ETag {etag}"
+        )))
+    }
+}
+
+/// Matches `*` or any entry of a comma-separated list of entity tags.
+fn etag_matches(value: &HeaderValue, etag: &str) -> bool {
+    value.to_str().is_ok_and(|value| {
+        value.trim() == "*" || value.split(',').any(|tag| tag.trim() == etag)
+    })
+}
+
+fn parse_contact_id(id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(id).map_err(|_| AppError::InvalidUuid("invalid contact id".to_string()))
+}
+
+/// The `:id` path segment, rejected with `invalid_uuid` instead of axum's
+/// plain-text path error when it isn't a UUID.
+struct ContactId(Uuid);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ContactId
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+        parse_contact_id(&id).map(ContactId)
+    }
+}
+
+/// Loads a contact and its phone numbers, locking the row for the rest of the
+/// transaction.
+async fn lock_contact(conn: &mut PgConnection, id: Uuid) -> Result<Contact, sqlx::Error> {
+    let mut contact = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+    load_related(&mut *conn, slice::from_mut(&mut contact)).await?;
+
+    Ok(contact)
+}
+
+/// Serves `load` through the Redis cache, when there is one.
+async fn cached<T, F>(state: &AppState, key: &str, load: F) -> Result<T, AppError>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<T, AppError>>,
+{
+    match &state.redis {
+        Some(redis) => redis.get_or_load(key, load).await,
+        None => load.await,
+    }
+}
+
+async fn fetch_contact(state: &AppState, id: Uuid) -> Result<Contact, AppError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let mut contact = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE id = $1 AND deleted_at IS NULL"
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    load_related(&mut conn, slice::from_mut(&mut contact)).await?;
+
+    Ok(contact)
+}
+
+/// `PUT` replaces the whole contact: every field must be supplied, and
+/// omitted optional fields (`email`, `birthday`, `notes`, `company`,
+/// `country_code`, `avatar_url`, `address`, `phones`, `tags`) are cleared. An
+/// omitted `is_favorite` resets to `false`.
+#[utoipa::path(
+    put,
+    path = "/contacts/{id}",
+    tag = "contacts",
+    params(
+        ("id" = Uuid, Path, description = "Contact id"),
+        ("If-Match" = Option<String>, Header, description = "Only replace if the ETag still matches")
+    ),
+    request_body = CreateContact,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = Contact),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 409, description = "The phone number is taken", body = ErrorBody),
+        (status = 412, description = "`If-Match` no longer matches", body = ErrorBody),
+        (status = 422, description = "Invalid fields", body = ErrorBody)
+    )
+)]
+async fn replace_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+    headers: HeaderMap,
+    Json(payload): Json<CreateContact>,
+) -> Result<Response, AppError> {
+    payload.validate()?;
+    let (phone, phones) = resolve_phones(&payload)?;
+    let address = payload.address();
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let current = lock_contact(&mut tx, id).await?;
+    check_if_match(&headers, &current)?;
+
+    let mut contact = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET
+            first_name = $1,
+            last_name = $2,
+            phone = $3,
+            email = $4,
+            birthday = $5,
+            notes = $6,
+            company = $7,
+            country_code = $8,
+            avatar_url = $9,
+            street = $10,
+            city = $11,
+            state = $12,
+            postal_code = $13,
+            country = $14,
+            is_favorite = $15,
+            version = version + 1,
+            updated_at = NOW()
+        WHERE id = $16
+        RETURNING *
+        "#
+    )
+    .bind(payload.first_name.trim())
+    .bind(payload.last_name.trim())
+    .bind(&phone)
+    .bind(payload.email.as_deref().map(str::trim))
+    .bind(payload.birthday)
+    .bind(payload.notes.as_deref().map(str::trim))
+    .bind(normalize_company(payload.company.as_deref()))
+    .bind(normalize_country_code(payload.country_code.as_deref()))
+    .bind(normalize_avatar_url(payload.avatar_url.as_deref()))
+    .bind(&address.street)
+    .bind(&address.city)
+    .bind(&address.state)
+    .bind(&address.postal_code)
+    .bind(&address.country)
+    .bind(payload.is_favorite.unwrap_or(false))
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    replace_phones(&mut tx, id, &phones).await?;
+    contact.phones = phones;
+
+    let tags = normalize_tags(payload.tags.as_deref().unwrap_or_default());
+    replace_tags(&mut tx, id, &tags).await?;
+    contact.tags = tags;
+
+    audit::record(
+        &mut tx,
+        &actor,
+        id,
+        Action::Update,
+        Some(&current),
+        Some(&contact),
+    )
+    .await?;
+    tx.commit().await?;
+    state.events.publish(Event::Updated, &contact);
+
+    Ok(with_etag(contact))
+}
+
+/// Creates or replaces the contact whose primary number is `phone`, for sync
+/// clients that key contacts by number. The body is a full contact, as for
+/// `PUT /contacts/{id}`, and its `phone` must be the same number as the path.
+/// The write is a single `INSERT ... ON CONFLICT`, so concurrent requests for
+/// a new number can't create it twice.
+#[utoipa::path(
+    put,
+    path = "/contacts/by-phone/{phone}",
+    tag = "contacts",
+    params(("phone" = String, Path, description = "Primary phone number, in any format accepted on create")),
+    request_body = CreateContact,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Replaced the existing contact", body = Contact),
+        (status = 201, description = "Created; `Location` points at the new contact", body = Contact),
+        (status = 422, description = "Invalid fields, or a `phone` that differs from the path", body = ErrorBody)
+    )
+)]
+async fn upsert_contact_by_phone(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(number): Path<String>,
+    Json(payload): Json<CreateContact>,
+) -> Result<Response, AppError> {
+    payload.validate()?;
+    let (primary, phones) = resolve_phones(&payload)?;
+    let region = payload
+        .country_code
+        .as_deref()
+        .and_then(phone::parse_region);
+    let number = normalize_phone(&number, region)?;
+    if primary != number {
+        return Err(AppError::Validation(format!(
+            "`phone` ({primary}) must match the number in the path ({number})"
+        )));
+    }
+    let address = payload.address();
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let mut previous = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE phone = $1 AND deleted_at IS NULL FOR UPDATE"
+    )
+    .bind(&number)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(previous) = &mut previous {
+        load_related(&mut tx, slice::from_mut(previous)).await?;
+    }
+
+    // `xmax` is only zero for a freshly inserted row version.
+    let row = sqlx::query(
+        r#"
+        INSERT INTO contacts (
+            id, first_name, last_name, phone, email, birthday, notes, company, country_code,
+            avatar_url, street, city, state, postal_code, country, is_favorite
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        ON CONFLICT (phone) WHERE deleted_at IS NULL DO UPDATE
+        SET
+            first_name = EXCLUDED.first_name,
+            last_name = EXCLUDED.last_name,
+            email = EXCLUDED.email,
+            birthday = EXCLUDED.birthday,
+            notes = EXCLUDED.notes,
+            company = EXCLUDED.company,
+            country_code = EXCLUDED.country_code,
+            avatar_url = EXCLUDED.avatar_url,
+            street = EXCLUDED.street,
+            city = EXCLUDED.city,
+            state = EXCLUDED.state,
+            postal_code = EXCLUDED.postal_code,
+            country = EXCLUDED.country,
+            is_favorite = EXCLUDED.is_favorite,
+            version = contacts.version + 1,
+            updated_at = NOW()
+        RETURNING *, xmax = 0 AS inserted
+        "#
+    )
+    .bind(Uuid::new_v4())
+    .bind(payload.first_name.trim())
+    .bind(payload.last_name.trim())
+    .bind(&number)
+    .bind(payload.email.as_deref().map(str::trim))
+    .bind(payload.birthday)
+    .bind(payload.notes.as_deref().map(str::trim))
+    .bind(normalize_company(payload.company.as_deref()))
+    .bind(normalize_country_code(payload.country_code.as_deref()))
+    .bind(normalize_avatar_url(payload.avatar_url.as_deref()))
+    .bind(&address.street)
+    .bind(&address.city)
+    .bind(&address.state)
+    .bind(&address.postal_code)
+    .bind(&address.country)
+    .bind(payload.is_favorite.unwrap_or(false))
+    .fetch_one(&mut *tx)
+    .await?;
+    let inserted: bool = row.try_get("inserted")?;
+    let mut contact = Contact::from_row(&row)?;
+
+    replace_phones(&mut tx, contact.id, &phones).await?;
+    contact.phones = phones;
+
+    let tags = normalize_tags(payload.tags.as_deref().unwrap_or_default());
+    replace_tags(&mut tx, contact.id, &tags).await?;
+    contact.tags = tags;
+
+    let action = if inserted {
+        Action::Create
+    } else {
+        Action::Update
+    };
+    audit::record(
+        &mut tx,
+        &actor,
+        contact.id,
+        action,
+        previous.as_ref(),
+        Some(&contact),
+    )
+    .await?;
+    tx.commit().await?;
+
+    if inserted {
+        state.events.publish(Event::Created, &contact);
+        created_response(StatusCode::CREATED, contact)
+    } else {
+        state.events.publish(Event::Updated, &contact);
+        Ok(with_etag(contact))
+    }
+}
+
+/// `PATCH` merges only the fields present in the body.
+#[utoipa::path(
+    patch,
+    path = "/contacts/{id}",
+    tag = "contacts",
+    params(
+        ("id" = Uuid, Path, description = "Contact id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the ETag still matches")
+    ),
+    request_body = UpdateContact,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = Contact),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 409, description = "Stale `version` or the phone number is taken", body = ErrorBody),
+        (status = 412, description = "`If-Match` no longer matches", body = ErrorBody),
+        (status = 422, description = "Invalid fields", body = ErrorBody)
+    )
+)]
+async fn update_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateContact>,
+) -> Result<Response, AppError> {
+    payload.validate()?;
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let contact = lock_contact(&mut tx, id).await?;
+    check_if_match(&headers, &contact)?;
+    let updated_contact = apply_update(&mut tx, &actor, contact, payload).await?;
+    tx.commit().await?;
+    state.events.publish(Event::Updated, &updated_contact);
+    
+    Ok(with_etag(updated_contact))
+}
+
+/// Merges `payload` into `contact`, which must be locked, and records the
+/// change.
+async fn apply_update(
+    conn: &mut PgConnection,
+    actor: &Actor,
+    mut contact: Contact,
+    payload: UpdateContact,
+) -> Result<Contact, AppError> {
+    let id = contact.id;
+    let previous = contact.clone();
+    
+    if let Some(country_code) = payload.country_code {
+        contact.country_code = normalize_country_code(country_code.as_deref());
+    }
+    let region = contact
+        .country_code
+        .as_deref()
+        .and_then(phone::parse_region);
+    if let Some(first_name) = payload.first_name {
+        contact.first_name = first_name.trim().to_string();
+    }
+    if let Some(last_name) = payload.last_name {
+        contact.last_name = last_name.trim().to_string();
+    }
+    let phone_given = payload.phone.is_some();
+    if let Some(phone) = payload.phone {
+        contact.phone = normalize_phone(&phone, region)?;
+    }
+    if let Some(email) = payload.email {
+        contact.email = email.map(|email| email.trim().to_string());
+    }
+    if let Some(birthday) = payload.birthday {
+        contact.birthday = birthday;
+    }
+    if let Some(notes) = payload.notes {
+        contact.notes = notes.map(|notes| notes.trim().to_string());
+    }
+    if let Some(company) = payload.company {
+        contact.company = normalize_company(company.as_deref()).map(str::to_string);
+    }
+    if let Some(avatar_url) = payload.avatar_url {
+        contact.avatar_url = normalize_avatar_url(avatar_url.as_deref());
+    }
+    if let Some(address) = payload.address {
+        contact.address = address.map(|address| address.normalized());
+    }
+    if let Some(is_favorite) = payload.is_favorite {
+        contact.is_favorite = is_favorite;
+    }
+
+    let phones = match payload.phones {
+        Some(phones) => {
+            let phones = normalize_phone_list(&phones, region)?;
+            // Dropping the primary number from the list promotes the first
+            // remaining one, unless a new primary was given explicitly.
+            if !phone_given && !phones.iter().any(|p| p.number == contact.phone) {
+                if let Some(first) = phones.first() {
+                    contact.phone = first.number.clone();
+                }
+            }
+            Some(with_primary(phones, &contact.phone))
+        }
+        None if contact.phone != previous.phone => {
+            let mut phones = contact.phones.clone();
+            match phones.iter_mut().find(|p| p.number == previous.phone) {
+                Some(primary) => primary.number = contact.phone.clone(),
+                None => phones = with_primary(phones, &contact.phone),
+            }
+            Some(phones)
+        }
+        None => None,
+    };
+    
+    let address = contact.address.clone().unwrap_or_default();
+    let mut updated_contact = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET 
+            first_name = $1,
+            last_name = $2,
+            phone = $3,
+            email = $4,
+            birthday = $5,
+            notes = $6,
+            company = $7,
+            country_code = $8,
+            avatar_url = $9,
+            street = $10,
+            city = $11,
+            state = $12,
+            postal_code = $13,
+            country = $14,
+            is_favorite = $15,
+            version = version + 1,
+            updated_at = NOW()
+        WHERE id = $16 AND ($17::INTEGER IS NULL OR version = $17)
+        RETURNING *
+        "#
+    )
+    .bind(&contact.first_name)
+    .bind(&contact.last_name)
+    .bind(&contact.phone)
+    .bind(&contact.email)
+    .bind(contact.birthday)
+    .bind(&contact.notes)
+    .bind(&contact.company)
+    .bind(&contact.country_code)
+    .bind(&contact.avatar_url)
+    .bind(&address.street)
+    .bind(&address.city)
+    .bind(&address.state)
+    .bind(&address.postal_code)
+    .bind(&address.country)
+    .bind(contact.is_favorite)
+    .bind(id)
+    .bind(payload.version)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| {
+        AppError::Conflict(format!(
+            "contact has been modified (current version is {}); re-fetch and retry",
+            contact.version
+        ))
+    })?;
+
+    updated_contact.phones = match phones {
+        Some(phones) => {
+            replace_phones(conn, id, &phones).await?;
+            phones
+        }
+        None => contact.phones,
+    };
+    updated_contact.tags = match payload.tags {
+        Some(tags) => {
+            let tags = normalize_tags(&tags);
+            replace_tags(conn, id, &tags).await?;
+            tags
+        }
+        None => contact.tags,
+    };
+
+    audit::record(
+        conn,
+        actor,
+        id,
+        Action::Update,
+        Some(&previous),
+        Some(&updated_contact),
+    )
+    .await?;
+
+    Ok(updated_contact)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/contacts/{id}",
+    tag = "contacts",
+    params(
+        ("id" = Uuid, Path, description = "Contact id"),
+        ("If-Match" = Option<String>, Header, description = "Only delete if the ETag still matches")
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "Soft-deleted"),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 412, description = "`If-Match` no longer matches", body = ErrorBody)
+    )
+)]
+async fn delete_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let current = lock_contact(&mut tx, id).await?;
+    check_if_match(&headers, &current)?;
+
+    let mut deleted = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET deleted_at = NOW(), version = version + 1
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    deleted.phones = current.phones.clone();
+    deleted.tags = current.tags.clone();
+
+    audit::record(
+        &mut tx,
+        &actor,
+        id,
+        Action::Delete,
+        Some(&current),
+        Some(&deleted),
+    )
+    .await?;
+    tx.commit().await?;
+    state.events.publish(Event::Deleted, &deleted);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Undoes a soft delete. Restoring a contact that isn't deleted is a no-op.
+#[utoipa::path(
+    post,
+    path = "/contacts/{id}/restore",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = Contact),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 409, description = "The phone number was reused meanwhile", body = ErrorBody)
+    )
+)]
+async fn restore_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+) -> Result<Response, AppError> {
+    let mut tx = state.db_pool.begin().await?;
+    let deleted = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE"
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(mut deleted) = deleted else {
+        // Hand the connection back first; `fetch_contact` needs one too.
+        drop(tx);
+        return Ok(with_etag(fetch_contact(&state, id).await?));
+    };
+    load_related(&mut tx, slice::from_mut(&mut deleted)).await?;
+
+    let mut restored = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET deleted_at = NULL, version = version + 1
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    restored.phones = deleted.phones.clone();
+    restored.tags = deleted.tags.clone();
+
+    audit::record(
+        &mut tx,
+        &actor,
+        id,
+        Action::Restore,
+        Some(&deleted),
+        Some(&restored),
+    )
+    .await?;
+    tx.commit().await?;
+    state.events.publish(Event::Restored, &restored);
+
+    Ok(with_etag(restored))
+}
+
+#[utoipa::path(
+    post,
+    path = "/contacts/{id}/favorite",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = Contact),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+async fn favorite_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+) -> Result<Response, AppError> {
+    set_favorite(&state, &actor, id, true).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/contacts/{id}/favorite",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = Contact),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+async fn unfavorite_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+) -> Result<Response, AppError> {
+    set_favorite(&state, &actor, id, false).await
+}
+
+/// Stars or unstars a contact. Repeating the same request is a no-op and
+/// leaves the version alone.
+async fn set_favorite(
+    state: &AppState,
+    actor: &Actor,
+    id: Uuid,
+    is_favorite: bool,
+) -> Result<Response, AppError> {
+    let mut tx = state.db_pool.begin().await?;
+    let current = lock_contact(&mut tx, id).await?;
+    if current.is_favorite == is_favorite {
+        return Ok(with_etag(current));
+    }
+
+    let mut updated = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET is_favorite = $2, version = version + 1, updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(is_favorite)
+    .fetch_one(&mut *tx)
+    .await?;
+    updated.phones = current.phones.clone();
+    updated.tags = current.tags.clone();
+
+    audit::record(
+        &mut tx,
+        actor,
+        id,
+        Action::Update,
+        Some(&current),
+        Some(&updated),
+    )
+    .await?;
+    tx.commit().await?;
+    state.events.publish(Event::Updated, &updated);
+
+    Ok(with_etag(updated))
+}
+
+/// Records that the user just got in touch with the contact.
+#[utoipa::path(
+    post,
+    path = "/contacts/{id}/touch",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "`last_contacted_at` is now", body = Contact),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+async fn touch_contact(
+    State(state): State<AppState>,
+    actor: Actor,
+    ContactId(id): ContactId,
+) -> Result<Response, AppError> {
+    let mut tx = state.db_pool.begin().await?;
+    let current = lock_contact(&mut tx, id).await?;
+
+    let mut updated = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET last_contacted_at = NOW(), version = version + 1, updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    updated.phones = current.phones.clone();
+    updated.tags = current.tags.clone();
+
+    audit::record(
+        &mut tx,
+        &actor,
+        id,
+        Action::Update,
+        Some(&current),
+        Some(&updated),
+    )
+    .await?;
+    tx.commit().await?;
+    state.events.publish(Event::Updated, &updated);
+
+    Ok(with_etag(updated))
+}
+
+const DEFAULT_STALE_DAYS: i32 = 90;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct StaleQuery {
+    /// Contacts not touched for at least this many days (default 90).
+    days: Option<i32>,
+    /// Default 50, max 200.
+    limit: Option<i64>,
+}
+
+/// Contacts not contacted in the last `days` days, or never, for following
+/// up. Never-contacted ones come first, then the longest untouched.
+#[utoipa::path(
+    get,
+    path = "/contacts/stale",
+    tag = "contacts",
+    params(StaleQuery),
+    responses(
+        (status = 200, body = Vec<Contact>),
+        (status = 400, description = "Negative `days` or `limit`", body = ErrorBody)
+    )
+)]
+async fn stale_contacts(
+    State(state): State<AppState>,
+    Query(query): Query<StaleQuery>,
+) -> Result<Json<Vec<Contact>>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_STALE_DAYS);
+    if days < 0 {
+        return Err(AppError::BadRequest(
+            "`days` must not be negative".to_string(),
+        ));
+    }
+    let (limit, _) = Pagination {
+        limit: query.limit,
+        offset: None,
+        after: None,
+    }
+    .resolve()?;
+
+    let mut conn = state.db_pool.acquire().await?;
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        r#"
+        SELECT * FROM contacts
+        WHERE deleted_at IS NULL
+            AND (last_contacted_at IS NULL OR last_contacted_at < NOW() - make_interval(days => $1))
+        ORDER BY last_contacted_at NULLS FIRST, first_name, last_name, id
+        LIMIT $2
+        "#
+    )
+    .bind(days)
+    .bind(limit)
+    .fetch_all(&mut *conn)
+    .await?;
+    load_related(&mut conn, &mut contacts).await?;
+
+    Ok(Json(contacts))
+}
+
+/// Redirects to the contact's profile picture, so `<img>` tags can point at
+/// the API.
+#[utoipa::path(
+    get,
+    path = "/contacts/{id}/avatar",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    responses(
+        (status = 302, description = "`Location` is the stored `avatar_url`"),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, description = "No such contact, or it has no avatar", body = ErrorBody)
+    )
+)]
+async fn contact_avatar(
+    State(state): State<AppState>,
+    ContactId(id): ContactId,
+) -> Result<Response, AppError> {
+    let avatar_url: Option<String> =
+        sqlx::query_scalar("SELECT avatar_url FROM contacts WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
+    // Normalized again for URLs stored before they were normalized on write.
+    let avatar_url = normalize_avatar_url(avatar_url.as_deref())
+        .ok_or_else(|| AppError::NotFound("Contact has no avatar".to_string()))?;
+
+    Ok((StatusCode::FOUND, [(header::LOCATION, avatar_url)]).into_response())
+}
+
+/// The contact as a vCard 3.0 file, the same one `GET /contacts/:id.vcf`
+/// serves.
+#[utoipa::path(
+    get,
+    path = "/contacts/{id}/vcard",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    responses(
+        (status = 200, description = "A vCard 3.0 file", body = String, content_type = "text/vcard"),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+async fn contact_vcard(
+    State(state): State<AppState>,
+    ContactId(id): ContactId,
+) -> Result<Response, AppError> {
+    Ok(vcard_file(&fetch_contact(&state, id).await?))
+}
+
+fn vcard_file(contact: &Contact) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, vcard::CONTENT_TYPE.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.vcf\"", contact.id),
+            ),
+        ],
+        vcard::to_vcard(contact),
+    )
+        .into_response()
+}
+
+const DEFAULT_QR_SCALE: u32 = 8;
+const MAX_QR_SCALE: u32 = 32;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct QrQuery {
+    /// Pixels per QR module, from 1 to 32; defaults to 8.
+    size: Option<u32>,
+}
+
+/// The contact's vCard as a QR code, which phone cameras offer to save as a
+/// new contact.
+#[utoipa::path(
+    get,
+    path = "/contacts/{id}/qr",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id"), QrQuery),
+    responses(
+        (status = 200, description = "A PNG image", body = Vec<u8>, content_type = "image/png"),
+        (status = 400, description = "Malformed id or `size` out of range", body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, description = "The vCard is too large for a QR code", body = ErrorBody)
+    )
+)]
+async fn contact_qr(
+    State(state): State<AppState>,
+    ContactId(id): ContactId,
+    Query(query): Query<QrQuery>,
+) -> Result<Response, AppError> {
+    let scale = query.size.unwrap_or(DEFAULT_QR_SCALE);
+    if !(1..=MAX_QR_SCALE).contains(&scale) {
+        return Err(AppError::BadRequest(format!(
+            "`size` must be between 1 and {MAX_QR_SCALE}"
+        )));
+    }
+
+    let contact = fetch_contact(&state, id).await?;
+    let png = qr::to_png(&vcard::to_vcard(&contact), scale)?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+/// The audit trail of a contact, oldest change first. Deleted contacts keep
+/// their history, including duplicates removed by a merge.
+#[utoipa::path(
+    get,
+    path = "/contacts/{id}/history",
+    tag = "contacts",
+    params(("id" = Uuid, Path, description = "Contact id")),
+    responses(
+        (status = 200, body = Vec<AuditEntry>),
+        (status = 400, description = "Malformed id", body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+async fn contact_history(
+    State(state): State<AppState>,
+    ContactId(id): ContactId,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let entries = audit::history(&mut conn, id).await?;
+
+    // Contacts created before the audit log existed have no entries yet.
+    if entries.is_empty() {
+        sqlx::query("SELECT 1 FROM contacts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await?;
+    }
+
+    Ok(Json(entries))
+}
+
+/// A live `text/event-stream` of committed changes. Each event is named
+/// after its kind (`contact.created`, ...) and carries the same JSON as a
+/// webhook. A client that falls too far behind gets a `lagged` event with the
+/// number of events it missed and should re-fetch what it shows.
+#[utoipa::path(
+    get,
+    path = "/contacts/events",
+    tag = "contacts",
+    responses((status = 200, description = "Server-sent events", body = String, content_type = "text/event-stream"))
+)]
+async fn contact_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<sse::Event, axum::Error>>> {
+    let mut subscription = state.events.subscribe();
+    let stream = async_stream::stream! {
+        while let Some(received) = subscription.recv().await {
+            yield match received {
+                Received::Event(event) => {
+                    sse::Event::default().event(event.event).json_data(&*event)
+                }
+                Received::Lagged(skipped) => {
+                    Ok(sse::Event::default().event("lagged").data(skipped.to_string()))
+                }
+            };
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+const MAX_BULK_DELETE: usize = 1000;
+const MAX_BULK_UPDATE: usize = 1000;
+
+#[derive(Deserialize, ToSchema)]
+struct BulkUpdate {
+    ids: Vec<Uuid>,
+    /// Merged into every listed contact as by `PATCH /contacts/{id}`.
+    changes: UpdateContact,
+}
+
+#[derive(Serialize, ToSchema)]
+struct UpdatedCount {
+    updated: u64,
+}
+
+/// Applies the same changes to many contacts in one transaction; if any of
+/// them fails, e.g. because a phone number is taken, none is updated. Ids of
+/// missing or deleted contacts are skipped.
+#[utoipa::path(
+    patch,
+    path = "/contacts",
+    tag = "contacts",
+    request_body = BulkUpdate,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = UpdatedCount),
+        (status = 400, description = "More than 1000 ids, or a `version` in `changes`", body = ErrorBody),
+        (status = 409, description = "A phone number is taken", body = ErrorBody),
+        (status = 422, description = "Invalid changes", body = ErrorBody)
+    )
+)]
+async fn update_contacts(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<BulkUpdate>,
+) -> Result<Json<UpdatedCount>, AppError> {
+    if payload.ids.len() > MAX_BULK_UPDATE {
+        return Err(AppError::BadRequest(format!(
+            "at most {MAX_BULK_UPDATE} ids can be updated per request"
+        )));
+    }
+    if payload.changes.version.is_some() {
+        return Err(AppError::BadRequest(
+            "`version` only applies to single-contact updates".to_string(),
+        ));
+    }
+    payload.changes.validate()?;
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // Lock in id order so concurrent bulk changes can't deadlock.
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        r#"
+        SELECT * FROM contacts
+        WHERE id = ANY($1) AND deleted_at IS NULL
+        ORDER BY id
+        FOR UPDATE
+        "#
+    )
+    .bind(&payload.ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    load_related(&mut tx, &mut contacts).await?;
+
+    let mut updated = Vec::with_capacity(contacts.len());
+    for contact in contacts {
+        updated.push(apply_update(&mut tx, &actor, contact, payload.changes.clone()).await?);
+    }
+    tx.commit().await?;
+    for contact in &updated {
+        state.events.publish(Event::Updated, contact);
+    }
+
+    Ok(Json(UpdatedCount {
+        updated: updated.len() as u64,
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+struct DeletedCount {
+    deleted: u64,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/contacts",
+    tag = "contacts",
+    request_body = Vec<Uuid>,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = DeletedCount),
+        (status = 400, description = "More than 1000 ids", body = ErrorBody)
+    )
+)]
+async fn delete_contacts(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(ids): Json<Vec<Uuid>>,
+) -> Result<Json<DeletedCount>, AppError> {
+    if ids.len() > MAX_BULK_DELETE {
+        return Err(AppError::BadRequest(format!(
+            "at most {MAX_BULK_DELETE} ids can be deleted per request"
+        )));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // Lock in id order so concurrent bulk deletes can't deadlock.
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        r#"
+        SELECT * FROM contacts
+        WHERE id = ANY($1) AND deleted_at IS NULL
+        ORDER BY id
+        FOR UPDATE
+        "#
+    )
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    load_related(&mut tx, &mut contacts).await?;
+
+    let locked_ids: Vec<Uuid> = contacts.iter().map(|contact| contact.id).collect();
+    let mut deleted = sqlx::query_as::<_, Contact>(
+        r#"
+        WITH deleted AS (
+            UPDATE contacts
+            SET deleted_at = NOW(), version = version + 1
+            WHERE id = ANY($1)
+            RETURNING *
+        )
+        SELECT * FROM deleted ORDER BY id
+        "#
+    )
+    .bind(&locked_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for (current, deleted) in contacts.iter().zip(&mut deleted) {
+        deleted.phones = current.phones.clone();
+        deleted.tags = current.tags.clone();
+        audit::record(
+            &mut tx,
+            &actor,
+            current.id,
+            Action::Delete,
+            Some(current),
+            Some(deleted),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    for contact in &deleted {
+        state.events.publish(Event::Deleted, contact);
+    }
+
+    Ok(Json(DeletedCount {
+        deleted: deleted.len() as u64,
+    }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct TrashQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+/// Soft-deleted contacts, most recently deleted first. They can be brought
+/// back with `POST /contacts/{id}/restore` until they are purged.
+#[utoipa::path(
+    get,
+    path = "/contacts/trash",
+    tag = "contacts",
+    params(TrashQuery),
+    responses(
+        (status = 200, body = Vec<Contact>),
+        (status = 400, description = "Negative `limit` or `offset`", body = ErrorBody)
+    )
+)]
+async fn list_trash(
+    State(state): State<AppState>,
+    Query(query): Query<TrashQuery>,
+) -> Result<Json<Vec<Contact>>, AppError> {
+    let (limit, offset) = Pagination {
+        limit: query.limit,
+        offset: query.offset,
+        after: None,
+    }
+    .resolve()?;
+
+    let mut conn = state.db_pool.acquire().await?;
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        r#"
+        SELECT * FROM contacts
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC, id
+        LIMIT $1 OFFSET $2
+        "#
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&mut *conn)
+    .await?;
+    load_related(&mut conn, &mut contacts).await?;
+
+    Ok(Json(contacts))
+}
+
+#[derive(Serialize, ToSchema)]
+struct PurgedCount {
+    purged: u64,
+}
+
+/// Permanently removes contacts that were deleted more than
+/// `TRASH_RETENTION_DAYS` ago, with their phone numbers and tags. Their
+/// change history is kept.
+#[utoipa::path(
+    delete,
+    path = "/contacts/trash/purge",
+    tag = "contacts",
+    security(("bearer" = []), ("api_key" = [])),
+    responses((status = 200, body = PurgedCount))
+)]
+async fn purge_trash(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> Result<Json<PurgedCount>, AppError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    // Snapshots for the audit log have to be taken before the cascade
+    // removes the phone numbers and tags.
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        r#"
+        SELECT * FROM contacts
+        WHERE deleted_at < NOW() - make_interval(days => $1)
+        ORDER BY id
+        FOR UPDATE
+        "#
+    )
+    .bind(state.config.trash_retention_days as i32)
+    .fetch_all(&mut *tx)
+    .await?;
+    load_related(&mut tx, &mut contacts).await?;
+
+    let ids: Vec<Uuid> = contacts.iter().map(|contact| contact.id).collect();
+    sqlx::query("DELETE FROM contacts WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+
+    for contact in &contacts {
+        audit::record(
+            &mut tx,
+            &actor,
+            contact.id,
+            Action::Purge,
+            Some(contact),
+            None,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(Json(PurgedCount {
+        purged: contacts.len() as u64,
+    }))
+}
+
+/// Streams the contacts matching the same filters as `GET /contacts` as CSV,
+/// one row at a time, so the table is never buffered in memory. Without
+/// filters that's every contact.
+#[utoipa::path(
+    get,
+    path = "/contacts/export.csv",
+    tag = "contacts",
+    params(ListFilter),
+    responses(
+        (status = 200, description = "The matching contacts as CSV", body = String, content_type = "text/csv"),
+        (status = 400, description = "Invalid filter", body = ErrorBody)
+    )
+)]
+async fn export_contacts_csv(
+    State(state): State<AppState>,
+    MultiQuery(filter): MultiQuery<ListFilter>,
+) -> Result<Response, AppError> {
+    let mut select = QueryBuilder::new("SELECT * FROM contacts");
+    filter.push_where(&mut select)?;
+    select.push(" ORDER BY first_name, last_name, id");
+
+    let pool = state.db_pool.clone();
+    let rows = async_stream::try_stream! {
+        yield export::csv_header()?;
+
+        let mut contacts = select.build_query_as::<Contact>().fetch(&pool);
+        while let Some(contact) = contacts.try_next().await? {
+            yield export::csv_row(&contact)?;
+        }
+    };
+
+    Ok(attachment("text/csv; charset=utf-8", "contacts.csv", rows))
+}
+
+const EXPORT_BATCH: i64 = 500;
+
+/// Every contact with its phone numbers and tags, in name order. Contacts are
+/// read in keyset-paginated batches so exports never buffer the whole table.
+fn contact_batches(
+    pool: Pool<Postgres>,
+    include_deleted: bool,
+) -> impl Stream<Item = Result<Vec<Contact>, sqlx::Error>> {
+    async_stream::try_stream! {
+        let mut last: Option<(String, String, Uuid)> = None;
+        loop {
+            let mut conn = pool.acquire().await?;
+            let mut batch = sqlx::query_as::<_, Contact>(
+                r#"
+                SELECT * FROM contacts
+                WHERE ($5 OR deleted_at IS NULL)
+                    AND ($1::uuid IS NULL OR (first_name, last_name, id) > ($2, $3, $1))
+                ORDER BY first_name, last_name, id
+                LIMIT $4
+                "#
+            )
+            .bind(last.as_ref().map(|(_, _, id)| *id))
+            .bind(last.as_ref().map(|(first_name, _, _)| first_name.clone()))
+            .bind(last.as_ref().map(|(_, last_name, _)| last_name.clone()))
+            .bind(EXPORT_BATCH)
+            .bind(include_deleted)
+            .fetch_all(&mut *conn)
+            .await?;
+            load_related(&mut conn, &mut batch).await?;
+            drop(conn);
+
+            let next = batch
+                .last()
+                .filter(|_| batch.len() as i64 == EXPORT_BATCH)
+                .map(|contact| {
+                    (contact.first_name.clone(), contact.last_name.clone(), contact.id)
+                });
+            yield batch;
+            match next {
+                Some(next) => last = Some(next),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Streams every contact as one multi-card vCard file, the format phones
+/// import from.
+#[utoipa::path(
+    get,
+    path = "/contacts/export.vcf",
+    tag = "contacts",
+    responses((status = 200, description = "All contacts as vCard 3.0", body = String, content_type = "text/vcard"))
+)]
+async fn export_contacts_vcf(State(state): State<AppState>) -> Response {
+    let cards = contact_batches(state.db_pool.clone(), false).map_ok(|batch| {
+        batch
+            .iter()
+            .map(vcard::to_vcard)
+            .collect::<String>()
+            .into_bytes()
+    });
+
+    attachment(
+        vcard::CONTENT_TYPE,
+        "contacts.vcf",
+        cards.map_err(BoxError::from),
+    )
+}
+
+/// Streams a backup of the whole table, soft-deleted contacts included, as a
+/// pretty-printed JSON array that `POST /contacts/import.json` restores.
+#[utoipa::path(
+    get,
+    path = "/contacts/export.json",
+    tag = "contacts",
+    responses((status = 200, description = "Every contact, including soft-deleted ones", body = Vec<Contact>))
+)]
+async fn export_contacts_json(State(state): State<AppState>) -> Response {
+    let pool = state.db_pool.clone();
+    let items = async_stream::try_stream! {
+        let mut batches = pin!(contact_batches(pool, true));
+        let mut first = true;
+        yield b"[".to_vec();
+        while let Some(batch) = batches.try_next().await? {
+            for contact in &batch {
+                yield export::json_item(contact, first)?;
+                first = false;
+            }
+        }
+        yield if first { b"]\n".to_vec() } else { b"\n]\n".to_vec() };
+    };
+
+    attachment("application/json", "contacts.json", items)
+}
+
+/// A downloadable response streaming `body`.
+fn attachment<S>(content_type: &str, filename: &str, body: S) -> Response
+where
+    S: Stream<Item = Result<Vec<u8>, BoxError>> + Send + 'static,
+{
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum OnImportError {
+    #[default]
+    Skip,
+    Abort,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ImportOptions {
+    #[serde(default)]
+    #[param(inline)]
+    on_error: OnImportError,
+    /// Check every row, including for duplicates, then roll back instead of
+    /// saving anything.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ImportRowError {
+    line: u64,
+    error: String,
+}
+
+#[derive(Serialize, Default, ToSchema)]
+struct ImportSummary {
+    imported: usize,
+    skipped: usize,
+    errors: Vec<ImportRowError>,
+}
+
+/// Imports contacts from a CSV uploaded as the `file` field of a multipart
+/// form. All rows are inserted in one transaction; with `on_error=skip`
+/// (the default) bad rows are reported by line number and left out, with
+/// `on_error=abort` the first bad row rolls back the whole import. With
+/// `dry_run=true` the transaction is always rolled back, so the summary
+/// previews the import without changing anything.
+#[utoipa::path(
+    post,
+    path = "/contacts/import",
+    tag = "contacts",
+    params(ImportOptions),
+    request_body(
+        content = String,
+        content_type = "multipart/form-data",
+        description = "A CSV file in the `file` field"
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = ImportSummary),
+        (status = 400, description = "Missing file or unreadable CSV header", body = ErrorBody),
+        (status = 409, description = "With `on_error=abort`: a duplicate phone", body = ErrorBody),
+        (status = 422, description = "With `on_error=abort`: an invalid row", body = ErrorBody)
+    )
+)]
+async fn import_contacts_csv(
+    State(state): State<AppState>,
+    actor: Actor,
+    Query(options): Query<ImportOptions>,
+    mut multipart: Multipart,
+) -> Result<Json<ImportSummary>, AppError> {
+    let data = read_upload(&mut multipart, "file").await?;
+    let rows = import::parse_csv(&data).map_err(AppError::BadRequest)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let mut summary = ImportSummary::default();
+    let mut imported = Vec::new();
+
+    for row in rows {
+        let result = match row.contact {
+            Ok(contact) => import_row(&mut tx, &actor, &contact).await,
+            Err(e) => Err(AppError::Validation(e)),
+        };
+
+        match result {
+            Ok(contact) => imported.push(contact),
+            Err(e @ AppError::Internal(_)) => return Err(e),
+            Err(e) if options.on_error == OnImportError::Abort => {
+                let message = format!("line {}: {e}", row.line);
+                return Err(match e {
+                    AppError::Conflict(_) => AppError::Conflict(message),
+                    _ => AppError::Validation(message),
+                });
+            }
+            Err(e) => {
+                summary.skipped += 1;
+                summary.errors.push(ImportRowError {
+                    line: row.line,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    summary.imported = imported.len();
+    if options.dry_run {
+        tx.rollback().await?;
+        return Ok(Json(summary));
+    }
+
+    tx.commit().await?;
+    for contact in &imported {
+        state.events.publish(Event::Created, contact);
+    }
+
+    Ok(Json(summary))
+}
+
+/// Validates and inserts one imported row inside a savepoint, so a rejected
+/// row doesn't poison the surrounding transaction.
+async fn import_row(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    actor: &Actor,
+    contact: &CreateContact,
+) -> Result<Contact, AppError> {
+    contact.validate()?;
+    let (phone, phones) = resolve_phones(contact)?;
+
+    let mut savepoint = tx.begin().await?;
+    match insert_contact(&mut savepoint, actor, contact, &phone, &phones).await {
+        Ok(created) => {
+            savepoint.commit().await?;
+            Ok(created)
+        }
+        Err(e) => {
+            savepoint.rollback().await?;
+            Err(e.into())
+        }
+    }
+}
+
+/// Restores a backup written by `GET /contacts/export.json`. Contacts keep
+/// their ids, timestamps, versions, and soft-deletion, so a backup
+/// round-trips exactly; ones whose id already exists are skipped, which makes
+/// restoring the same backup twice harmless. Everything is inserted in one
+/// transaction, so a phone number taken by a different contact rolls the
+/// whole restore back.
+#[utoipa::path(
+    post,
+    path = "/contacts/import.json",
+    tag = "contacts",
+    request_body = Vec<Contact>,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "`skipped` counts ids that already existed", body = ImportSummary),
+        (status = 409, description = "A phone number is taken by another contact", body = ErrorBody),
+        (status = 422, description = "Invalid fields, keyed like `contacts[0].email`", body = ErrorBody)
+    )
+)]
+async fn import_contacts_json(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(contacts): Json<Vec<Contact>>,
+) -> Result<Json<ImportSummary>, AppError> {
+    let mut errors = FieldErrors::new();
+    let mut restores = Vec::with_capacity(contacts.len());
+    for (index, contact) in contacts.into_iter().enumerate() {
+        let payload = contact.to_create();
+        let mut contact_errors = payload.field_errors();
+        // Numbers are only read once the country code they may depend on
+        // is known to be valid.
+        let phones = if contact_errors.is_empty() {
+            resolve_phone_fields(&payload, &mut contact_errors)
+        } else {
+            None
+        };
+        errors.extend(
+            contact_errors
+                .into_iter()
+                .map(|(field, error)| (format!("contacts[{index}].{field}"), error)),
+        );
+        if let Some((phone, phones)) = phones {
+            restores.push((contact, payload, phone, phones));
+        }
+    }
+    invalid_fields(errors)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let mut summary = ImportSummary::default();
+    let mut imported = Vec::new();
+
+    for (contact, payload, phone, phones) in restores {
+        let address = payload.address();
+        let restored = sqlx::query_as::<_, Contact>(
+            r#"
+            INSERT INTO contacts (
+                id, first_name, last_name, phone, email, birthday, notes, company,
+                country_code, avatar_url, street, city, state, postal_code, country,
+                is_favorite, last_contacted_at, created_at, updated_at, version, deleted_at
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                $18, $19, $20, $21
+            )
+            ON CONFLICT (id) DO NOTHING
+            RETURNING *
+            "#
+        )
+        .bind(contact.id)
+        .bind(payload.first_name.trim())
+        .bind(payload.last_name.trim())
+        .bind(&phone)
+        .bind(payload.email.as_deref().map(str::trim))
+        .bind(payload.birthday)
+        .bind(payload.notes.as_deref().map(str::trim))
+        .bind(normalize_company(payload.company.as_deref()))
+        .bind(normalize_country_code(payload.country_code.as_deref()))
+        .bind(normalize_avatar_url(payload.avatar_url.as_deref()))
+        .bind(&address.street)
+        .bind(&address.city)
+        .bind(&address.state)
+        .bind(&address.postal_code)
+        .bind(&address.country)
+        .bind(contact.is_favorite)
+        .bind(contact.last_contacted_at)
+        .bind(contact.created_at)
+        .bind(contact.updated_at)
+        .bind(contact.version)
+        .bind(contact.deleted_at)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(mut restored) = restored else {
+            summary.skipped += 1;
+            continue;
+        };
+
+        replace_phones(&mut tx, restored.id, &phones).await?;
+        restored.phones = phones;
+        let tags = normalize_tags(&contact.tags);
+        replace_tags(&mut tx, restored.id, &tags).await?;
+        restored.tags = tags;
+
+        audit::record(
+            &mut tx,
+            &actor,
+            restored.id,
+            Action::Create,
+            None,
+            Some(&restored),
+        )
+        .await?;
+        imported.push(restored);
+    }
+
+    tx.commit().await?;
+    for contact in imported
+        .iter()
+        .filter(|contact| contact.deleted_at.is_none())
+    {
+        state.events.publish(Event::Created, contact);
+    }
+    summary.imported = imported.len();
+
+    Ok(Json(summary))
+}
+
+/// Reads the contents of the multipart field called `name`.
+async fn read_upload(multipart: &mut Multipart, name: &str) -> Result<Bytes, AppError> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        if field.name() == Some(name) {
+            return field
+                .bytes()
+                .await
+                .map_err(|e| AppError::BadRequest(e.to_string()));
+        }
+    }
+
+    Err(AppError::BadRequest(format!(
+        "multipart form must contain a `{name}` field"
+    )))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SearchQuery {
+    /// Matches any of a contact's numbers, as set by `match`.
+    phone: Option<String>,
+    /// Exactly four digits matched against the end of every number, for
+    /// "who called me" lookups.
+    last4: Option<String>,
+    /// Part of the first or the last name, ignoring case.
+    name: Option<String>,
+    /// Part of the first name, ignoring case.
+    first_name: Option<String>,
+    /// Part of the last name, ignoring case.
+    last_name: Option<String>,
+    /// Part of the company name, ignoring case.
+    company: Option<String>,
+    /// Part of the email address, ignoring case.
+    email: Option<String>,
+    /// Full-text search over notes, e.g. `conference` or `"met at" -work`.
+    notes: Option<String>,
+    #[serde(rename = "match", default)]
+    #[param(inline)]
+    phone_match: PhoneMatch,
+    /// Report where `name`, `first_name`, and `last_name` matched.
+    #[serde(default)]
+    highlight: bool,
+}
+
+/// How `?phone=` is compared against stored numbers.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum PhoneMatch {
+    /// The full number, normalized to E.164 first.
+    #[default]
+    Exact,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+impl SearchQuery {
+    /// Where the name criteria matched `contact`.
+    fn highlights(&self, contact: &Contact) -> Highlights {
+        let name = self.name.as_deref();
+        let first_name: Vec<&str> = name.into_iter().chain(self.first_name.as_deref()).collect();
+        let last_name: Vec<&str> = name.into_iter().chain(self.last_name.as_deref()).collect();
+        Highlights {
+            first_name: highlight::find(&contact.first_name, &first_name),
+            last_name: highlight::find(&contact.last_name, &last_name),
+        }
+    }
+
+    /// Appends one `AND` condition per given criterion, each value as a bound
+    /// parameter, and returns how many there were.
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) -> Result<usize, AppError> {
+        let mut criteria = 0;
+
+        if let Some(phone) = &self.phone {
+            let (operator, value) = phone_pattern(phone, self.phone_match)?;
+            push_phone_condition(query, operator, value);
+            criteria += 1;
+        }
+        if let Some(last4) = &self.last4 {
+            if last4.len() != 4 || !last4.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(AppError::Validation(
+                    "`last4` must be exactly four digits".to_string(),
+                ));
+            }
+            push_phone_condition(query, "LIKE", format!("%{last4}"));
+            criteria += 1;
+        }
+        if let Some(name) = &self.name {
+            let pattern = format!("%{}%", escape_like(name.trim()));
+            query
+                .push(" AND (first_name ILIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR last_name ILIKE ")
+                .push_bind(pattern)
+                .push(")");
+            criteria += 1;
+        }
+
+        let substrings = [
+            ("first_name", &self.first_name),
+            ("last_name", &self.last_name),
+            ("company", &self.company),
+            ("email", &self.email),
+        ];
+        for (column, value) in substrings {
+            if let Some(value) = value {
+                query
+                    .push(format_args!(" AND {column} ILIKE "))
+                    .push_bind(format!("%{}%", escape_like(value.trim())));
+                criteria += 1;
+            }
+        }
+
+        if let Some(notes) = &self.notes {
+            query
+                .push(" AND notes_tsv @@ websearch_to_tsquery('english', ")
+                .push_bind(notes.trim().to_string())
+                .push(")");
+            criteria += 1;
+        }
+
+        Ok(criteria)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct SearchResult {
+    #[serde(flatten)]
+    contact: Contact,
+    /// Only with `highlight=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    highlights: Option<Highlights>,
+}
+
+/// The parts of each name matched by the search, for a UI to emphasize.
+#[derive(Serialize, ToSchema)]
+struct Highlights {
+    first_name: Vec<highlight::Span>,
+    last_name: Vec<highlight::Span>,
+}
+
+/// Combines any of the criteria with `AND`. Results are in name order, or
+/// best notes matches first when `notes` is given.
+#[utoipa::path(
+    get,
+    path = "/contacts/search",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, body = Vec<SearchResult>),
+        (status = 400, description = "No search criteria given", body = ErrorBody),
+        (status = 422, description = "Invalid phone number or `last4`", body = ErrorBody)
+    )
+)]
+async fn search_contacts(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<Json<Vec<SearchResult>>, AppError> {
+    let mut select = QueryBuilder::new("SELECT * FROM contacts WHERE deleted_at IS NULL");
+    if query.push_conditions(&mut select)? == 0 {
+        return Err(AppError::BadRequest(
+            "at least one of `phone`, `last4`, `name`, `first_name`, `last_name`, `company`, \
+             `email`, or `notes` must be provided"
+                .to_string(),
+        ));
+    }
+    match &query.notes {
+        // `websearch_to_tsquery` accepts quoted phrases, `or`, and `-word`
+        // and never fails on malformed input.
+        Some(notes) => select
+            .push(" ORDER BY ts_rank(notes_tsv, websearch_to_tsquery('english', ")
+            .push_bind(notes.trim().to_string())
+            .push(")) DESC, first_name, last_name"),
+        None => select.push(" ORDER BY first_name, last_name"),
+    };
+
+    let key = format!("search:{}", raw_query.unwrap_or_default());
+    let contacts = cached(&state, &key, async {
+        let mut conn = state.db_pool.acquire().await?;
+        let mut contacts = select
+            .build_query_as::<Contact>()
+            .fetch_all(&mut *conn)
+            .await?;
+        load_related(&mut conn, &mut contacts).await?;
+        Ok(contacts)
+    })
+    .await?;
+
+    let results = contacts
+        .into_iter()
+        .map(|contact| SearchResult {
+            highlights: query.highlight.then(|| query.highlights(&contact)),
+            contact,
+        })
+        .collect();
+    Ok(Json(results))
+}
+
+/// The `(operator, value)` comparing stored numbers against `?phone=`.
+fn phone_pattern(phone: &str, phone_match: PhoneMatch) -> Result<(&'static str, String), AppError> {
+    Ok(match phone_match {
+        PhoneMatch::Exact => ("=", normalize_phone(phone, None)?),
+        PhoneMatch::Prefix => {
+            // Stored numbers are E.164, so a bare prefix starts after the `+`.
+            let mut partial = partial_phone(phone)?;
+            if !partial.starts_with('+') {
+                partial.insert(0, '+');
+            }
+            ("LIKE", format!("{}%", escape_like(&partial)))
+        }
+        PhoneMatch::Suffix => ("LIKE", format!("%{}", escape_like(&partial_phone(phone)?))),
+        PhoneMatch::Contains => ("LIKE", format!("%{}%", escape_like(&partial_phone(phone)?))),
+    })
+}
+
+/// Matches the primary number or any other number of the contact.
+fn push_phone_condition(query: &mut QueryBuilder<'_, Postgres>, operator: &str, value: String) {
+    query
+        .push(format_args!(" AND (phone {operator} "))
+        .push_bind(value.clone())
+        .push(format_args!(
+            " OR EXISTS (SELECT 1 FROM phone_numbers p \
+             WHERE p.contact_id = contacts.id AND p.number {operator} "
+        ))
+        .push_bind(value)
+        .push("))");
+}
+
+/// Strips formatting characters from a partial phone number.
+fn partial_phone(input: &str) -> Result<String, AppError> {
+    let partial: String = input
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+    if partial.is_empty() {
+        return Err(AppError::Validation(
+            "phone number must not be empty".to_string(),
+        ));
+    }
+    Ok(partial)
+}
+
+/// Escapes `LIKE` wildcards so user input matches literally.
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.3;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct FuzzyQuery {
+    q: String,
+    threshold: Option<f32>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize, FromRow, ToSchema)]
+struct ScoredContact {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    contact: Contact,
+    /// Trigram similarity between the query and the full name, from 0 to 1.
+    similarity: f32,
+}
+
+/// Typo-tolerant name search using `pg_trgm` trigram similarity, best
+/// matches first.
+#[utoipa::path(
+    get,
+    path = "/contacts/fuzzy",
+    tag = "search",
+    params(FuzzyQuery),
+    responses(
+        (status = 200, body = Vec<ScoredContact>),
+        (status = 400, description = "Empty `q` or out-of-range `threshold`", body = ErrorBody)
+    )
+)]
+async fn fuzzy_search_contacts(
+    State(state): State<AppState>,
+    Query(query): Query<FuzzyQuery>,
+) -> Result<Json<Vec<ScoredContact>>, AppError> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(AppError::BadRequest("`q` must not be empty".to_string()));
+    }
+    let threshold = query.threshold.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(AppError::BadRequest(
+            "`threshold` must be between 0 and 1".to_string(),
+        ));
+    }
+    let (limit, _) = Pagination {
+        limit: query.limit,
+        offset: None,
+        after: None,
+    }
+    .resolve()?;
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // The `%` operator can use the trigram index, but takes its cutoff from
+    // this setting rather than a parameter.
+    sqlx::query("SELECT set_config('pg_trgm.similarity_threshold', $1, true)")
+        .bind(threshold.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    let results = sqlx::query_as::<_, ScoredContact>(
+        r#"
+        SELECT *, similarity(first_name || ' ' || last_name, $1) AS similarity
+        FROM contacts
+        WHERE deleted_at IS NULL AND (first_name || ' ' || last_name) % $1
+        ORDER BY similarity DESC, first_name, last_name
+        LIMIT $2
+        "#
+    )
+    .bind(q)
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let (mut contacts, scores): (Vec<Contact>, Vec<f32>) = results
+        .into_iter()
+        .map(|result| (result.contact, result.similarity))
+        .unzip();
+    load_related(&mut tx, &mut contacts).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(
+        contacts
+            .into_iter()
+            .zip(scores)
+            .map(|(contact, similarity)| ScoredContact {
+                contact,
+                similarity,
+            })
+            .collect(),
+    ))
+}
+
+const DEFAULT_SUGGEST_LIMIT: i64 = 10;
+const MAX_SUGGEST_LIMIT: i64 = 50;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SuggestQuery {
+    q: String,
+    limit: Option<i64>,
+}
+
+/// Just enough of a contact to render a typeahead entry.
+#[derive(Serialize, FromRow, ToSchema)]
+struct ContactSuggestion {
+    id: Uuid,
+    first_name: String,
+    last_name: String,
+    phone: String,
+}
+
+/// Autocomplete on first or last name prefixes. Matching on `lower(...)`
+/// rather than `ILIKE` lets the prefix indexes serve the query.
+#[utoipa::path(
+    get,
+    path = "/contacts/suggest",
+    tag = "search",
+    params(SuggestQuery),
+    responses(
+        (status = 200, body = Vec<ContactSuggestion>),
+        (status = 400, description = "`limit` below 1", body = ErrorBody)
+    )
+)]
+async fn suggest_contacts(
+    State(state): State<AppState>,
+    Query(query): Query<SuggestQuery>,
+) -> Result<Json<Vec<ContactSuggestion>>, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_SUGGEST_LIMIT);
+    if limit < 1 {
+        return Err(AppError::BadRequest(
+            "`limit` must be at least 1".to_string(),
+        ));
+    }
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+
+    let suggestions = sqlx::query_as::<_, ContactSuggestion>(
+        r#"
+        SELECT id, first_name, last_name, phone
+        FROM contacts
+        WHERE deleted_at IS NULL
+            AND (lower(first_name) LIKE $1 OR lower(last_name) LIKE $1)
+        ORDER BY first_name, last_name
+        LIMIT $2
+        "#
+    )
+    .bind(format!("{}%", escape_like(&q.to_lowercase())))
+    .bind(limit.min(MAX_SUGGEST_LIMIT))
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(suggestions))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ValidatePhoneQuery {
+    number: String,
+    /// ISO 3166-1 alpha-2 code for reading national formats, e.g. `NL`.
+    region: Option<String>,
+}
+
+/// Outcome of `GET /contacts/validate-phone`: either the normalized number
+/// and what it is, or why it was rejected.
+#[derive(Serialize, ToSchema)]
+struct PhoneValidation {
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    e164: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    phone_type: Option<PhoneType>,
+    /// Region the number is assigned to, e.g. `US`; `null` for numbers that
+    /// aren't tied to one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+/// Checks a phone number the way contact creation would, without storing
+/// anything.
+#[utoipa::path(
+    get,
+    path = "/contacts/validate-phone",
+    tag = "contacts",
+    params(ValidatePhoneQuery),
+    responses(
+        (status = 200, body = PhoneValidation),
+        (status = 400, description = "Unknown `region`", body = ErrorBody)
+    )
+)]
+async fn validate_phone(
+    Query(query): Query<ValidatePhoneQuery>,
+) -> Result<Json<PhoneValidation>, AppError> {
+    let region = match query.region.as_deref() {
+        Some(code) => Some(phone::parse_region(code).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "unknown region `{code}`; expected an ISO 3166-1 alpha-2 code such as NL"
+            ))
+        })?),
+        None => None,
+    };
+
+    let validation = match normalize_phone(&query.number, region) {
+        Ok(e164) => PhoneValidation {
+            valid: true,
+            phone_type: Some(PhoneType::of(&e164)),
+            region: phone::region_of(&e164).map(|id| id.as_ref().to_string()),
+            e164: Some(e164),
+            reason: None,
+        },
+        Err(e) => PhoneValidation {
+            valid: false,
+            e164: None,
+            phone_type: None,
+            region: None,
+            reason: Some(e.to_string()),
+        },
+    };
+    Ok(Json(validation))
+}
+
+#[derive(Serialize, FromRow, ToSchema)]
+struct ContactStats {
+    total: i64,
+    created_last_7_days: i64,
+    updated_last_7_days: i64,
+    favorites_count: i64,
+    /// `null` when there are no contacts.
+    #[sqlx(flatten, try_from = "RecentlyUpdatedColumns")]
+    most_recently_updated: Option<RecentlyUpdated>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct RecentlyUpdated {
+    id: Uuid,
+    first_name: String,
+    last_name: String,
+    updated_at: DateTime<Utc>,
+}
+
+/// The `recent_*` columns of the stats query, all `NULL` without contacts.
+#[derive(FromRow)]
+struct RecentlyUpdatedColumns {
+    recent_id: Option<Uuid>,
+    recent_first_name: Option<String>,
+    recent_last_name: Option<String>,
+    recent_updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<RecentlyUpdatedColumns> for Option<RecentlyUpdated> {
+    fn from(columns: RecentlyUpdatedColumns) -> Self {
+        Some(RecentlyUpdated {
+            id: columns.recent_id?,
+            first_name: columns.recent_first_name?,
+            last_name: columns.recent_last_name?,
+            updated_at: columns.recent_updated_at?,
+        })
+    }
+}
+
+/// Dashboard counts, computed in a single scan, along with the contact
+/// changed last.
+#[utoipa::path(
+    get,
+    path = "/contacts/stats",
+    tag = "contacts",
+    responses((status = 200, body = ContactStats))
+)]
+async fn contact_stats(State(state): State<AppState>) -> Result<Json<ContactStats>, AppError> {
+    let stats = sqlx::query_as::<_, ContactStats>(
+        r#"
+        SELECT
+            counts.*,
+            recent.id AS recent_id,
+            recent.first_name AS recent_first_name,
+            recent.last_name AS recent_last_name,
+            recent.updated_at AS recent_updated_at
+        FROM (
+            SELECT
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE created_at > NOW() - INTERVAL '7 days') AS created_last_7_days,
+                COUNT(*) FILTER (WHERE updated_at > NOW() - INTERVAL '7 days') AS updated_last_7_days,
+                COUNT(*) FILTER (WHERE is_favorite) AS favorites_count
+            FROM contacts
+            WHERE deleted_at IS NULL
+        ) AS counts
+        LEFT JOIN (
+            SELECT id, first_name, last_name, updated_at
+            FROM contacts
+            WHERE deleted_at IS NULL
+            ORDER BY updated_at DESC, id DESC
+            LIMIT 1
+        ) AS recent ON true
+        "#
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    Ok(Json(stats))
+}
+
+#[derive(Serialize, FromRow, ToSchema)]
+struct LetterCount {
+    /// Upper-cased first letter of `first_name`, or `#` for anything that
+    /// isn't a letter.
+    letter: String,
+    count: i64,
+}
+
+/// Contact counts per initial, for an A-Z jump list. Letters without contacts
+/// are omitted and `#` sorts last.
+#[utoipa::path(
+    get,
+    path = "/contacts/index",
+    tag = "contacts",
+    responses((status = 200, body = Vec<LetterCount>))
+)]
+async fn contact_index(State(state): State<AppState>) -> Result<Json<Vec<LetterCount>>, AppError> {
+    let letters = sqlx::query_as::<_, LetterCount>(
+        r#"
+        SELECT letter, COUNT(*) AS count
+        FROM (
+            SELECT CASE
+                WHEN left(first_name, 1) ~ '^[[:alpha:]]$' THEN upper(left(first_name, 1))
+                ELSE '#'
+            END AS letter
+            FROM contacts
+            WHERE deleted_at IS NULL
+        ) initials
+        GROUP BY letter
+        ORDER BY letter = '#', letter
+        "#
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(letters))
+}
+
+const MAX_RANDOM_COUNT: i64 = 50;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RandomQuery {
+    /// How many contacts to return; defaults to 1, at most 50.
+    count: Option<i64>,
+}
+
+/// A random sample of contacts, for "rediscover someone" features. Fewer
+/// are returned when there aren't enough contacts.
+#[utoipa::path(
+    get,
+    path = "/contacts/random",
+    tag = "contacts",
+    params(RandomQuery),
+    responses(
+        (status = 200, body = Vec<Contact>),
+        (status = 400, description = "`count` below 1", body = ErrorBody)
+    )
+)]
+async fn random_contacts(
+    State(state): State<AppState>,
+    Query(query): Query<RandomQuery>,
+) -> Result<Json<Vec<Contact>>, AppError> {
+    let count = query.count.unwrap_or(1);
+    if count < 1 {
+        return Err(AppError::BadRequest(
+            "`count` must be at least 1".to_string(),
+        ));
+    }
+
+    let mut conn = state.db_pool.acquire().await?;
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE deleted_at IS NULL ORDER BY RANDOM() LIMIT $1"
+    )
+    .bind(count.min(MAX_RANDOM_COUNT))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    load_related(&mut conn, &mut contacts).await?;
+
+    Ok(Json(contacts))
+}
+
+#[derive(Serialize, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum DuplicateKind {
+    /// The contacts share a phone number.
+    Phone,
+    /// The contacts have the same first and last name, ignoring case.
+    Name,
+}
+
+#[derive(Serialize, ToSchema)]
+struct DuplicateCluster {
+    kind: DuplicateKind,
+    /// The shared phone number, or the lower-cased full name.
+    key: String,
+    contacts: Vec<Contact>,
+}
+
+/// Groups of contacts that look like duplicates of each other, as candidates
+/// for merging. Nothing is modified.
+#[utoipa::path(
+    get,
+    path = "/contacts/duplicates",
+    tag = "contacts",
+    responses((status = 200, body = Vec<DuplicateCluster>))
+)]
+async fn find_duplicates(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DuplicateCluster>>, AppError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    // Primary numbers are unique, but a number can still be listed under
+    // several contacts.
+    let by_phone = sqlx::query_as::<_, (String, Vec<Uuid>)>(
+        r#"
+        SELECT p.number, array_agg(DISTINCT p.contact_id)
+        FROM phone_numbers p
+        JOIN contacts c ON c.id = p.contact_id
+        WHERE c.deleted_at IS NULL
+        GROUP BY p.number
+        HAVING COUNT(DISTINCT p.contact_id) > 1
+        ORDER BY p.number
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let by_name = sqlx::query_as::<_, (String, Vec<Uuid>)>(
+        r#"
+        SELECT lower(first_name) || ' ' || lower(last_name) AS full_name, array_agg(id)
+        FROM contacts
+        WHERE deleted_at IS NULL
+        GROUP BY full_name
+        HAVING COUNT(*) > 1
+        ORDER BY full_name
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let ids: Vec<Uuid> = by_phone
+        .iter()
+        .chain(&by_name)
+        .flat_map(|(_, ids)| ids.iter().copied())
+        .collect();
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        "SELECT * FROM contacts WHERE id = ANY($1) ORDER BY created_at"
+    )
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    load_related(&mut tx, &mut contacts).await?;
+
+    tx.commit().await?;
+
+    let contacts: HashMap<Uuid, Contact> = contacts
+        .into_iter()
+        .map(|contact| (contact.id, contact))
+        .collect();
+    let clusters = by_phone
+        .into_iter()
+        .map(|group| (DuplicateKind::Phone, group))
+        .chain(
+            by_name
+                .into_iter()
+                .map(|group| (DuplicateKind::Name, group)),
+        )
+        .map(|(kind, (key, ids))| {
+            let mut members: Vec<Contact> = ids
+                .iter()
+                .filter_map(|id| contacts.get(id).cloned())
+                .collect();
+            members.sort_by_key(|contact| contact.created_at);
+            DuplicateCluster {
+                kind,
+                key,
+                contacts: members,
+            }
+        })
+        .collect();
+
+    Ok(Json(clusters))
+}
+
+#[derive(Deserialize, ToSchema)]
+struct MergeContacts {
+    /// The contact that survives the merge.
+    primary_id: Uuid,
+    /// Contacts folded into the primary and then deleted.
+    duplicate_ids: Vec<Uuid>,
+}
+
+/// Folds duplicates into a primary contact: their numbers are appended to
+/// the primary's list, their tags are added, a missing email, birthday,
+/// notes, company, country code, avatar, or address is filled in from them,
+/// and the duplicates are then deleted permanently rather than soft-deleted,
+/// since their numbers now belong to the primary.
+#[utoipa::path(
+    post,
+    path = "/contacts/merge",
+    tag = "contacts",
+    request_body = MergeContacts,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The merged primary contact", body = Contact),
+        (status = 400, description = "No duplicates, or the primary listed among them", body = ErrorBody),
+        (status = 404, description = "One of the ids doesn't exist; nothing was changed", body = ErrorBody)
+    )
+)]
+async fn merge_contacts(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<MergeContacts>,
+) -> Result<Response, AppError> {
+    let mut duplicate_ids = payload.duplicate_ids;
+    duplicate_ids.sort_unstable();
+    duplicate_ids.dedup();
+    if duplicate_ids.is_empty() {
+        return Err(AppError::BadRequest(
+            "duplicate_ids must not be empty".to_string(),
+        ));
+    }
+    if duplicate_ids.contains(&payload.primary_id) {
+        return Err(AppError::BadRequest(
+            "primary_id must not be listed in duplicate_ids".to_string(),
+        ));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // Lock every row in id order so concurrent merges can't deadlock.
+    let mut ids = duplicate_ids.clone();
+    ids.push(payload.primary_id);
+    let mut contacts = sqlx::query_as::<_, Contact>(
+        r#"
+        SELECT * FROM contacts
+        WHERE id = ANY($1) AND deleted_at IS NULL
+        ORDER BY id
+        FOR UPDATE
+        "#
+    )
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let primary_index = contacts
+        .iter()
+        .position(|contact| contact.id == payload.primary_id)
+        .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", payload.primary_id)))?;
+    if let Some(missing) = duplicate_ids
+        .iter()
+        .find(|id| !contacts.iter().any(|contact| contact.id == **id))
+    {
+        return Err(AppError::NotFound(format!("Contact {missing} not found")));
+    }
+
+    load_related(&mut tx, &mut contacts).await?;
+    let primary = contacts.swap_remove(primary_index);
+    let original = primary.clone();
+    contacts.sort_by_key(|contact| contact.created_at);
+
+    let mut phones = primary.phones;
+    for phone in contacts.iter().flat_map(|contact| &contact.phones) {
+        if !phones.iter().any(|p| p.number == phone.number) {
+            phones.push(phone.clone());
+        }
+    }
+    let mut tags = primary.tags;
+    tags.extend(
+        contacts
+            .iter()
+            .flat_map(|contact| contact.tags.iter().cloned()),
+    );
+    let tags = normalize_tags(&tags);
+    let email = primary
+        .email
+        .or_else(|| contacts.iter().find_map(|contact| contact.email.clone()));
+    let birthday = primary
+        .birthday
+        .or_else(|| contacts.iter().find_map(|contact| contact.birthday));
+    let notes = primary
+        .notes
+        .or_else(|| contacts.iter().find_map(|contact| contact.notes.clone()));
+    let company = primary
+        .company
+        .or_else(|| contacts.iter().find_map(|contact| contact.company.clone()));
+    let country_code = primary.country_code.or_else(|| {
+        contacts
+            .iter()
+            .find_map(|contact| contact.country_code.clone())
+    });
+    let avatar_url = primary.avatar_url.or_else(|| {
+        contacts
+            .iter()
+            .find_map(|contact| contact.avatar_url.clone())
+    });
+    let address = primary
+        .address
+        .or_else(|| contacts.iter().find_map(|contact| contact.address.clone()))
+        .unwrap_or_default();
+    // Whichever of them was contacted most recently.
+    let last_contacted_at = contacts
+        .iter()
+        .filter_map(|contact| contact.last_contacted_at)
+        .chain(primary.last_contacted_at)
+        .max();
+
+    sqlx::query("DELETE FROM contacts WHERE id = ANY($1)")
+        .bind(&duplicate_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut merged = sqlx::query_as::<_, Contact>(
+        r#"
+        UPDATE contacts
+        SET
+            email = $2,
+            birthday = $3,
+            notes = $4,
+            company = $5,
+            country_code = $6,
+            avatar_url = $7,
+            street = $8,
+            city = $9,
+            state = $10,
+            postal_code = $11,
+            country = $12,
+            last_contacted_at = $13,
+            version = version + 1,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(primary.id)
+    .bind(&email)
+    .bind(birthday)
+    .bind(&notes)
+    .bind(&company)
+    .bind(&country_code)
+    .bind(&avatar_url)
+    .bind(&address.street)
+    .bind(&address.city)
+    .bind(&address.state)
+    .bind(&address.postal_code)
+    .bind(&address.country)
+    .bind(last_contacted_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    replace_phones(&mut tx, merged.id, &phones).await?;
+    merged.phones = phones;
+    replace_tags(&mut tx, merged.id, &tags).await?;
+    merged.tags = tags;
+
+    for duplicate in &contacts {
+        audit::record(
+            &mut tx,
+            &actor,
+            duplicate.id,
+            Action::Delete,
+            Some(duplicate),
+            None,
+        )
+        .await?;
+    }
+    audit::record(
+        &mut tx,
+        &actor,
+        merged.id,
+        Action::Update,
+        Some(&original),
+        Some(&merged),
+    )
+    .await?;
+    tx.commit().await?;
+    for duplicate in &contacts {
+        state.events.publish(Event::Deleted, duplicate);
+    }
+    state.events.publish(Event::Updated, &merged);
+
+    Ok(with_etag(merged))
+}
+
+const DEFAULT_BIRTHDAY_WINDOW_DAYS: i32 = 30;
+const MAX_BIRTHDAY_WINDOW_DAYS: i32 = 366;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct BirthdayQuery {
+    /// How many days ahead to look, including today (default 30, max 366).
+    days: Option<i32>,
+}
+
+#[derive(Serialize, FromRow, ToSchema)]
+struct UpcomingBirthday {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    contact: Contact,
+    next_birthday: NaiveDate,
+    /// `0` when the birthday is today.
+    days_until: i32,
+}
+
+/// Contacts whose birthday falls within the next `days` days, soonest first.
+/// Adding whole years to a date lands February 29 birthdays on February 28
+/// in common years.
+#[utoipa::path(
+    get,
+    path = "/contacts/birthdays/upcoming",
+    tag = "contacts",
+    params(BirthdayQuery),
+    responses(
+        (status = 200, body = Vec<UpcomingBirthday>),
+        (status = 400, description = "`days` out of range", body = ErrorBody)
+    )
+)]
+async fn upcoming_birthdays(
+    State(state): State<AppState>,
+    Query(query): Query<BirthdayQuery>,
+) -> Result<Json<Vec<UpcomingBirthday>>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_BIRTHDAY_WINDOW_DAYS);
+    if !(0..=MAX_BIRTHDAY_WINDOW_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!(
+            "`days` must be between 0 and {MAX_BIRTHDAY_WINDOW_DAYS}"
+        )));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let results = sqlx::query_as::<_, UpcomingBirthday>(
+        r#"
+        SELECT *, next_birthday - CURRENT_DATE AS days_until
+        FROM (
+            SELECT c.*,
+                CASE
+                    WHEN this_year >= CURRENT_DATE THEN this_year
+                    ELSE (c.birthday + make_interval(years => age + 1))::DATE
+                END AS next_birthday
+            FROM contacts c,
+                LATERAL (
+                    SELECT (date_part('year', CURRENT_DATE) - date_part('year', c.birthday))::INTEGER AS age
+                ) a,
+                LATERAL (SELECT (c.birthday + make_interval(years => age))::DATE AS this_year) t
+            WHERE c.deleted_at IS NULL AND c.birthday IS NOT NULL
+        ) upcoming
+        WHERE next_birthday <= CURRENT_DATE + $1
+        ORDER BY next_birthday, first_name, last_name
+        "#
+    )
+    .bind(days)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let (mut contacts, dates): (Vec<Contact>, Vec<(NaiveDate, i32)>) = results
+        .into_iter()
+        .map(|result| (result.contact, (result.next_birthday, result.days_until)))
+        .unzip();
+    load_related(&mut tx, &mut contacts).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(
+        contacts
+            .into_iter()
+            .zip(dates)
+            .map(|(contact, (next_birthday, days_until))| UpcomingBirthday {
+                contact,
+                next_birthday,
+                days_until,
+            })
+            .collect(),
+    ))
+}
+
+/// Bucket for contacts without a company in [`company_counts`].
+const NO_COMPANY: &str = "Uncategorized";
+
+#[derive(Serialize, FromRow, ToSchema)]
+struct CompanyCount {
+    /// `Uncategorized` for contacts without a company.
+    company: String,
+    count: i64,
+}
+
+/// Number of contacts per company, largest first. Spellings that differ only
+/// in case are counted together.
+#[utoipa::path(
+    get,
+    path = "/contacts/companies",
+    tag = "contacts",
+    responses((status = 200, body = Vec<CompanyCount>))
+)]
+async fn company_counts(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<CompanyCount>>, AppError> {
+    let counts = sqlx::query_as::<_, CompanyCount>(
+        r#"
+        SELECT COALESCE(MIN(NULLIF(btrim(company), '')), $1) AS company, COUNT(*) AS count
+        FROM contacts
+        WHERE deleted_at IS NULL
+        GROUP BY lower(NULLIF(btrim(company), ''))
+        ORDER BY count DESC, company
+        "#
+    )
+    .bind(NO_COMPANY)
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(counts))
+}
@@ -0,0 +1,79 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::error::AppError;
+
+pub const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+/// Representation chosen from the request's `Accept` header.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Xml,
+}
+
+impl Format {
+    /// Picks the supported media type with the highest `q` value (the first
+    /// one listed on ties), defaulting to JSON when `Accept` is missing. Fails
+    /// with `406 Not Acceptable` when nothing offered is supported.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, AppError> {
+        let Some(accept) = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.trim().is_empty())
+        else {
+            return Ok(Format::Json);
+        };
+
+        let mut best: Option<(f32, Format)> = None;
+        for range in accept.split(',') {
+            let mut params = range.split(';');
+            let media_type = params.next().unwrap_or_default().trim();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let format = match media_type.to_ascii_lowercase().as_str() {
+                "application/json" | "application/*" | "*/*" => Format::Json,
+                "application/xml" | "text/xml" => Format::Xml,
+                _ => continue,
+            };
+            let better = match best {
+                Some((best_quality, _)) => quality > best_quality,
+                None => true,
+            };
+            if quality > 0.0 && better {
+                best = Some((quality, format));
+            }
+        }
+
+        best.map(|(_, format)| format).ok_or_else(|| {
+            AppError::NotAcceptable(
+                "supported media types are application/json and application/xml".to_string(),
+            )
+        })
+    }
+
+    /// Serializes `value` in this format; XML documents use `root` as the
+    /// outermost element.
+    pub fn render<T: Serialize>(self, root: &str, value: T) -> Result<Response, AppError> {
+        let mut response = match self {
+            Format::Json => Json(value).into_response(),
+            Format::Xml => {
+                let body = quick_xml::se::to_string_with_root(root, &value)
+                    .map_err(|e| AppError::Internal(format!("failed to serialize XML: {e}")))?;
+                ([(header::CONTENT_TYPE, XML_CONTENT_TYPE)], body).into_response()
+            }
+        };
+        // Caches must not serve one representation to a client asking for the
+        // other.
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+        Ok(response)
+    }
+}
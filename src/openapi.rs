@@ -0,0 +1,156 @@
+use utoipa::{
+    openapi::{
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        Server,
+    },
+    Modify, OpenApi,
+};
+
+/// OpenAPI document served at `/api-docs/openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Phone Book API",
+        description = "REST API for managing phone contacts."
+    ),
+    servers((url = "/v1", description = "Version 1. Breaking changes ship under a new prefix")),
+    paths(
+        crate::list_contacts,
+        crate::head_contacts,
+        crate::create_contact,
+        crate::update_contacts,
+        crate::delete_contacts,
+        crate::list_trash,
+        crate::purge_trash,
+        crate::get_contact,
+        crate::head_contact,
+        crate::replace_contact,
+        crate::upsert_contact_by_phone,
+        crate::update_contact,
+        crate::delete_contact,
+        crate::restore_contact,
+        crate::contact_history,
+        crate::contact_avatar,
+        crate::contact_vcard,
+        crate::contact_qr,
+        crate::contact_events,
+        crate::favorite_contact,
+        crate::unfavorite_contact,
+        crate::touch_contact,
+        crate::stale_contacts,
+        crate::create_contacts_batch,
+        crate::export_contacts_csv,
+        crate::export_contacts_vcf,
+        crate::export_contacts_json,
+        crate::import_contacts_csv,
+        crate::import_contacts_json,
+        crate::search_contacts,
+        crate::fuzzy_search_contacts,
+        crate::suggest_contacts,
+        crate::validate_phone,
+        crate::contact_stats,
+        crate::contact_index,
+        crate::random_contacts,
+        crate::find_duplicates,
+        crate::merge_contacts,
+        crate::upcoming_birthdays,
+        crate::company_counts,
+        crate::login,
+        crate::current_user,
+        crate::applied_migrations,
+        crate::api_usage,
+        crate::health,
+        crate::livez,
+        crate::metrics,
+    ),
+    components(schemas(
+        crate::Contact,
+        crate::PhoneNumber,
+        crate::phone::PhoneType,
+        crate::address::Address,
+        crate::CreateContact,
+        crate::UpdateContact,
+        crate::PaginatedContacts,
+        crate::BulkUpdate,
+        crate::UpdatedCount,
+        crate::DeletedCount,
+        crate::PurgedCount,
+        crate::ImportSummary,
+        crate::ImportRowError,
+        crate::ScoredContact,
+        crate::SearchResult,
+        crate::Highlights,
+        crate::highlight::Span,
+        crate::ContactSuggestion,
+        crate::PhoneValidation,
+        crate::ContactStats,
+        crate::RecentlyUpdated,
+        crate::LetterCount,
+        crate::DuplicateCluster,
+        crate::DuplicateKind,
+        crate::MergeContacts,
+        crate::UpcomingBirthday,
+        crate::CompanyCount,
+        crate::LoginRequest,
+        crate::TokenResponse,
+        crate::HealthStatus,
+        crate::PoolHealth,
+        crate::AppliedMigration,
+        crate::UsageReport,
+        crate::ClientUsage,
+        crate::audit::AuditEntry,
+        crate::auth::AuthUser,
+        crate::error::ErrorBody,
+    )),
+    modifiers(&SecurityAddon, &UnversionedPaths),
+    tags(
+        (name = "contacts", description = "Create, read, update, and delete contacts"),
+        (name = "search", description = "Look up contacts by phone or name"),
+        (name = "auth", description = "User login"),
+        (name = "health", description = "Probes and metrics"),
+        (name = "admin", description = "Operator endpoints, only open to the API key"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the two credentials accepted on protected routes.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+/// Health probes and metrics aren't versioned, so they override the `/v1`
+/// server with the root.
+struct UnversionedPaths;
+
+impl Modify for UnversionedPaths {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            let is_health = item.operations.values().any(|operation| {
+                operation
+                    .tags
+                    .as_ref()
+                    .is_some_and(|tags| tags.iter().any(|tag| tag == "health"))
+            });
+            if is_health {
+                item.servers = Some(vec![Server::new("/")]);
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use phonenumber::{country, metadata::DATABASE, Mode, Type};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug)]
+pub struct ValidationError(String);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Kind of line a number belongs to, as far as its numbering plan tells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneType {
+    Mobile,
+    FixedLine,
+    Voip,
+    /// Anything else, including plans such as North America's that don't
+    /// tell mobile and fixed-line numbers apart.
+    #[default]
+    Unknown,
+}
+
+impl PhoneType {
+    /// Classifies an E.164 number.
+    pub fn of(e164: &str) -> Self {
+        let Ok(number) = phonenumber::parse(None, e164) else {
+            return PhoneType::Unknown;
+        };
+        match number.number_type(&DATABASE) {
+            Type::Mobile => PhoneType::Mobile,
+            Type::FixedLine => PhoneType::FixedLine,
+            Type::Voip => PhoneType::Voip,
+            _ => PhoneType::Unknown,
+        }
+    }
+}
+
+/// Lets `phone_type` be decoded straight from the `phone` column.
+impl From<String> for PhoneType {
+    fn from(phone: String) -> Self {
+        PhoneType::of(&phone)
+    }
+}
+
+/// How numbers are written in responses; they are always stored as E.164.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneFormat {
+    /// `+12025550123`
+    #[default]
+    E164,
+    /// `+1 202-555-0123`
+    International,
+    /// `(202) 555-0123`, as dialed within the number's own country.
+    National,
+}
+
+impl PhoneFormat {
+    /// Writes an E.164 number in this format, or leaves it as is if it
+    /// doesn't parse.
+    pub fn apply(self, e164: &str) -> String {
+        let mode = match self {
+            PhoneFormat::E164 => return e164.to_string(),
+            PhoneFormat::International => Mode::International,
+            PhoneFormat::National => Mode::National,
+        };
+        match phonenumber::parse(None, e164) {
+            Ok(number) => number.format().mode(mode).to_string(),
+            Err(_) => e164.to_string(),
+        }
+    }
+}
+
+/// The region an E.164 number is assigned to; `None` for numbers that
+/// aren't tied to one, such as satellite services.
+pub fn region_of(e164: &str) -> Option<country::Id> {
+    phonenumber::parse(None, e164).ok()?.country().id()
+}
+
+/// Parses an ISO 3166-1 alpha-2 country code, in any case, into the region
+/// used for national-format numbers. `None` for codes without phone metadata.
+pub fn parse_region(code: &str) -> Option<country::Id> {
+    code.trim().to_ascii_uppercase().parse().ok()
+}
+
+/// Normalizes a user-supplied phone number to canonical E.164 (`+15551234567`).
+///
+/// Formatting characters are ignored. Input without a leading `+` is first
+/// read as a national number of `region`, if given, so `0555 123456` with NL
+/// becomes `+31555123456`. Failing that, it is treated as an international
+/// number whose country code was written without the plus.
+pub fn normalize_phone(
+    input: &str,
+    region: Option<country::Id>,
+) -> Result<String, ValidationError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ValidationError(
+            "phone number must not be empty".to_string(),
+        ));
+    }
+
+    if let Some(region) = region.filter(|_| !trimmed.starts_with('+')) {
+        if let Ok(number) = phonenumber::parse(Some(region), trimmed) {
+            if phonenumber::is_valid(&number) {
+                return Ok(number.format().mode(Mode::E164).to_string());
+            }
+        }
+    }
+
+    let candidate = if trimmed.starts_with('+') {
+        trimmed.to_string()
+    } else {
+        format!("+{trimmed}")
+    };
+
+    let number = phonenumber::parse(None, &candidate)
+        .map_err(|e| ValidationError(format!("invalid phone number: {e}")))?;
+
+    if !phonenumber::is_valid(&number) {
+        return Err(ValidationError(format!(
+            "invalid phone number: {trimmed} is not a valid number"
+        )));
+    }
+
+    Ok(number.format().mode(Mode::E164).to_string())
+}
@@ -0,0 +1,27 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, Luma};
+use qrcode::{types::QrError, QrCode};
+
+use crate::error::AppError;
+
+/// Renders `data` as a black-on-white QR code PNG, `scale` pixels per
+/// module, with the standard quiet zone around it.
+pub fn to_png(data: &str, scale: u32) -> Result<Vec<u8>, AppError> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| match e {
+        QrError::DataTooLong => {
+            AppError::Validation("the contact is too large to fit in a QR code".to_string())
+        }
+        e => AppError::Internal(format!("failed to encode QR code: {e}")),
+    })?;
+    let image = code
+        .render::<Luma<u8>>()
+        .module_dimensions(scale, scale)
+        .build();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("failed to write PNG: {e}")))?;
+    Ok(png)
+}
@@ -0,0 +1,132 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use axum::{extract::ConnectInfo, http::Request, response::IntoResponse};
+use governor::middleware::NoOpMiddleware;
+use tower_governor::{
+    governor::GovernorConfigBuilder, key_extractor::KeyExtractor, GovernorError, GovernorLayer,
+};
+
+use crate::error::AppError;
+
+/// How often idle per-client buckets are evicted from the limiter's state.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+pub type RateLimitLayer = GovernorLayer<ClientIp, NoOpMiddleware>;
+
+/// Keys each request on the client's IP address: the peer address, unless
+/// the peer is one of the trusted proxies.
+///
+/// Behind a trusted proxy the client is the rightmost `X-Forwarded-For` hop
+/// that isn't a trusted proxy itself, since proxies append to the header and
+/// everything left of their entries is whatever the client sent. Without
+/// `X-Forwarded-For`, `X-Real-IP` is used. Forwarded headers from any other
+/// peer are ignored, so a client can't pick a fresh key per request.
+#[derive(Clone)]
+pub struct ClientIp {
+    trusted_proxies: Arc<[IpAddr]>,
+}
+
+impl ClientIp {
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.contains(&ip)
+    }
+
+    fn forwarded_client<T>(&self, req: &Request<T>) -> Option<IpAddr> {
+        let headers = req.headers();
+        let forwarded_for = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        if !forwarded_for.is_empty() {
+            for hop in forwarded_for.iter().rev() {
+                match hop.parse::<IpAddr>() {
+                    Ok(ip) if self.is_trusted(ip) => continue,
+                    Ok(ip) => return Some(ip),
+                    // Nothing left of a garbled hop can be trusted either.
+                    Err(_) => return None,
+                }
+            }
+            return None;
+        }
+        headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+    }
+}
+
+impl KeyExtractor for ClientIp {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .ok_or(GovernorError::UnableToExtractKey)?;
+        if !self.is_trusted(peer) {
+            return Ok(peer);
+        }
+        Ok(self.forwarded_client(req).unwrap_or(peer))
+    }
+}
+
+/// Builds a per-client limiter allowing a burst of `per_minute` requests,
+/// refilled evenly over a minute.
+///
+/// `trusted_proxies` is a comma-separated list of proxy addresses whose
+/// forwarded headers name the client; see [`ClientIp`].
+pub fn layer(per_minute: u32, trusted_proxies: Option<&str>) -> anyhow::Result<RateLimitLayer> {
+    let trusted_proxies = trusted_proxies
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .map(|proxy| {
+            proxy
+                .parse::<IpAddr>()
+                .with_context(|| format!("TRUSTED_PROXIES entry `{proxy}` is not an IP address"))
+        })
+        .collect::<anyhow::Result<Arc<[IpAddr]>>>()?;
+
+    let config = GovernorConfigBuilder::default()
+        .key_extractor(ClientIp { trusted_proxies })
+        .period(Duration::from_secs(60) / per_minute)
+        .burst_size(per_minute)
+        .error_handler(|error| match error {
+            GovernorError::TooManyRequests { wait_time, .. } => AppError::RateLimited {
+                retry_after_secs: wait_time,
+            }
+            .into_response(),
+            GovernorError::UnableToExtractKey => {
+                AppError::BadRequest("unable to determine client address".to_string())
+                    .into_response()
+            }
+            GovernorError::Other { msg, .. } => {
+                AppError::Internal(msg.unwrap_or_else(|| "rate limiter failed".to_string()))
+                    .into_response()
+            }
+        })
+        .finish()
+        .expect("rate limit period and burst size are non-zero");
+    let config = Arc::new(config);
+
+    let limiter = config.limiter().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            limiter.retain_recent();
+        }
+    });
+
+    Ok(GovernorLayer { config })
+}
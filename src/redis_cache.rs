@@ -0,0 +1,130 @@
+use std::{fmt::Display, future::Future, time::Duration};
+
+use deadpool_redis::{
+    redis::{self, AsyncCommands, SetExpiry, SetOptions},
+    Config as PoolConfig, Pool, Runtime,
+};
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+/// Holds the current generation, which every cache key embeds.
+const GENERATION_KEY: &str = "phonebook:cache:generation";
+/// Carries each new generation to every instance.
+const INVALIDATION_CHANNEL: &str = "phonebook:cache:invalidate";
+/// How long to wait before subscribing again after losing the connection.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Responses cached in Redis and shared by every instance.
+///
+/// Writes don't track which entries they affect. Instead, each one replaces
+/// the generation that every key embeds, orphaning all earlier entries at
+/// once until they expire, and announces it so instances can also drop
+/// their in-process caches. Redis being unreachable only costs speed:
+/// failures are logged and treated as misses.
+#[derive(Clone)]
+pub struct RedisCache {
+    client: redis::Client,
+    pool: Pool,
+    ttl: Duration,
+}
+
+impl RedisCache {
+    /// Sets up a pool for `url`; connections are only opened when needed.
+    pub fn new(url: &str, ttl: Duration) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        let pool = PoolConfig::from_url(url).create_pool(Some(Runtime::Tokio1))?;
+        Ok(Self { client, pool, ttl })
+    }
+
+    /// The value cached under `key`, or the result of `load`, which is
+    /// cached in turn when it succeeds.
+    pub async fn get_or_load<T, E, F>(&self, key: &str, load: F) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T, E>>,
+    {
+        // Taken before loading, so a write in between leaves the result
+        // under a generation nobody reads anymore.
+        let Some(key) = logged(self.versioned_key(key).await) else {
+            return load.await;
+        };
+        if let Some(cached) = logged(self.get(&key).await).flatten() {
+            match serde_json::from_str(&cached) {
+                Ok(value) => return Ok(value),
+                Err(e) => tracing::warn!(key, error = %e, "discarding unreadable cache entry"),
+            }
+        }
+
+        let value = load.await?;
+        logged(self.set(&key, &value).await);
+        Ok(value)
+    }
+
+    /// Orphans every cached entry and tells all instances about it.
+    pub async fn invalidate(&self) {
+        logged(self.bump_generation().await);
+    }
+
+    /// Calls `on_invalidate` whenever any instance invalidates the cache, and
+    /// after reconnecting, since announcements may have been missed.
+    pub fn spawn_subscriber(&self, on_invalidate: impl Fn() + Send + 'static) {
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            loop {
+                match client.get_async_pubsub().await {
+                    Ok(mut pubsub) => match pubsub.subscribe(INVALIDATION_CHANNEL).await {
+                        Ok(()) => {
+                            let mut messages = pubsub.on_message();
+                            while messages.next().await.is_some() {
+                                on_invalidate();
+                            }
+                            tracing::warn!("lost the Redis cache invalidation subscription");
+                        }
+                        Err(e) => tracing::warn!(error = %e, "failed to subscribe to Redis"),
+                    },
+                    Err(e) => tracing::warn!(error = %e, "failed to connect to Redis"),
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+                on_invalidate();
+            }
+        });
+    }
+
+    async fn versioned_key(&self, key: &str) -> anyhow::Result<String> {
+        let mut conn = self.pool.get().await?;
+        let generation: Option<String> = conn.get(GENERATION_KEY).await?;
+        Ok(format!(
+            "phonebook:cache:{}:{key}",
+            generation.as_deref().unwrap_or("0")
+        ))
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let mut conn = self.pool.get().await?;
+        Ok(conn.get(key).await?)
+    }
+
+    async fn set<T: Serialize>(&self, key: &str, value: &T) -> anyhow::Result<()> {
+        let value = serde_json::to_string(value)?;
+        let mut conn = self.pool.get().await?;
+        let options = SetOptions::default().with_expiration(SetExpiry::EX(self.ttl.as_secs()));
+        let () = conn.set_options(key, value, options).await?;
+        Ok(())
+    }
+
+    async fn bump_generation(&self) -> anyhow::Result<()> {
+        let generation = Uuid::new_v4().to_string();
+        let mut conn = self.pool.get().await?;
+        let () = conn.set(GENERATION_KEY, &generation).await?;
+        let _receivers: usize = conn.publish(INVALIDATION_CHANNEL, &generation).await?;
+        Ok(())
+    }
+}
+
+/// Logs a failed cache operation, which requests carry on without.
+fn logged<T, E: Display>(result: Result<T, E>) -> Option<T> {
+    result
+        .map_err(|e| tracing::warn!(error = %e, "Redis cache unavailable"))
+        .ok()
+}
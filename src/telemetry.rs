@@ -0,0 +1,89 @@
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use sqlx::{Pool, Postgres};
+
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+const REQUESTS_TOTAL: &str = "http_requests_total";
+
+/// Latency buckets in seconds, from a fast lookup up to a slow CSV export.
+const REQUEST_DURATION_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0,
+];
+
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+static INSTALLED: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+
+/// Installs the global Prometheus recorder and returns the handle that
+/// renders the scrape payload. The recorder can only be set once per process,
+/// so later calls (from tests that build several apps) share the first one.
+pub fn install() -> anyhow::Result<PrometheusHandle> {
+    let mut installed = INSTALLED.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(handle) = &*installed {
+        return Ok(handle.clone());
+    }
+
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(REQUEST_DURATION.to_string()),
+            &REQUEST_DURATION_BUCKETS,
+        )?
+        .install_recorder()?;
+
+    // Histogram samples are buffered until drained, so drain them even if
+    // nothing scrapes for a while.
+    let upkeep = handle.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            upkeep.run_upkeep();
+        }
+    });
+
+    *installed = Some(handle.clone());
+    Ok(handle)
+}
+
+/// Records the latency and status class of every routed request, labelled
+/// with the route template (`/contacts/:id`) rather than the raw path to keep
+/// label cardinality bounded.
+pub async fn track(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |path| path.as_str().to_string());
+
+    let response = next.run(request).await;
+
+    let status_class = format!("{}xx", response.status().as_u16() / 100);
+    metrics::histogram!(REQUEST_DURATION, "method" => method.clone(), "route" => route.clone())
+        .record(start.elapsed().as_secs_f64());
+    metrics::counter!(
+        REQUESTS_TOTAL,
+        "method" => method,
+        "route" => route,
+        "status" => status_class
+    )
+    .increment(1);
+
+    response
+}
+
+/// Samples connection pool usage; called on each scrape so the gauges are
+/// always current.
+pub fn record_pool_stats(pool: &Pool<Postgres>) {
+    metrics::gauge!("db_pool_connections").set(f64::from(pool.size()));
+    metrics::gauge!("db_pool_idle_connections").set(pool.num_idle() as f64);
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+
+/// How often counts collected in memory are added to `api_usage`.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Requests counted per credential, kept in memory and periodically added
+/// to the `api_usage` table, which every instance shares.
+///
+/// Counting only touches a map, so requests never wait on the database for
+/// it. A crash loses at most the last [`FLUSH_INTERVAL`] of counts.
+#[derive(Clone, Default)]
+pub struct Usage {
+    pending: Arc<Mutex<HashMap<String, i64>>>,
+}
+
+impl Usage {
+    pub fn record(&self, client: &str) {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        match pending.get_mut(client) {
+            Some(count) => *count += 1,
+            None => {
+                pending.insert(client.to_string(), 1);
+            }
+        }
+    }
+
+    /// Adds the pending counts to the current month's totals. On failure
+    /// they're kept for the next attempt.
+    pub async fn flush(&self, pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+        let pending = mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let (clients, counts): (Vec<String>, Vec<i64>) = pending
+            .iter()
+            .map(|(client, count)| (client.clone(), *count))
+            .unzip();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO api_usage (client, period, requests)
+            SELECT client, date_trunc('month', NOW() AT TIME ZONE 'UTC')::DATE, requests
+            FROM UNNEST($1::TEXT[], $2::BIGINT[]) AS pending (client, requests)
+            ON CONFLICT (client, period) DO UPDATE
+            SET requests = api_usage.requests + EXCLUDED.requests
+            "#,
+        )
+        .bind(&clients)
+        .bind(&counts)
+        .execute(pool)
+        .await;
+
+        if result.is_err() {
+            let mut current = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            for (client, count) in pending {
+                *current.entry(client).or_default() += count;
+            }
+        }
+        result.map(|_| ())
+    }
+
+    /// Flushes every [`FLUSH_INTERVAL`] for as long as the server runs.
+    pub fn spawn_flusher(&self, pool: Pool<Postgres>) {
+        let usage = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = usage.flush(&pool).await {
+                    tracing::warn!(error = %e, "failed to record API usage");
+                }
+            }
+        });
+    }
+}
+
+/// Names an API key in usage reports without revealing it: the first eight
+/// hex digits of its SHA-256, so a rotated key gets a row of its own.
+pub fn api_key_client(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    let fingerprint: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+    format!("api_key:{fingerprint}")
+}
+
+/// Names a user who authenticated with a login token.
+pub fn user_client(username: &str) -> String {
+    format!("user:{username}")
+}
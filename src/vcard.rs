@@ -0,0 +1,88 @@
+use crate::Contact;
+
+pub const CONTENT_TYPE: &str = "text/vcard; charset=utf-8";
+
+/// Escapes a property value per RFC 2426 §4 (vCard 3.0).
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Maps a free-form phone label onto a vCard `TEL` type.
+fn tel_type(label: &str) -> &'static str {
+    match label.to_ascii_lowercase().as_str() {
+        "home" => "HOME",
+        "work" => "WORK",
+        "fax" => "FAX",
+        "pager" => "PAGER",
+        "mobile" | "cell" => "CELL",
+        _ => "VOICE",
+    }
+}
+
+/// Serializes a contact as a vCard 3.0 card with CRLF line endings.
+pub fn to_vcard(contact: &Contact) -> String {
+    let first_name = escape(&contact.first_name);
+    let last_name = escape(&contact.last_name);
+
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:3.0".to_string(),
+        format!("UID:{}", contact.id),
+        format!("N:{last_name};{first_name};;;"),
+        format!("FN:{first_name} {last_name}"),
+    ];
+    if contact.phones.is_empty() {
+        lines.push(format!("TEL;TYPE=CELL:{}", escape(&contact.phone)));
+    }
+    for phone in &contact.phones {
+        lines.push(format!(
+            "TEL;TYPE={}:{}",
+            tel_type(&phone.label),
+            escape(&phone.number)
+        ));
+    }
+    if let Some(email) = &contact.email {
+        lines.push(format!("EMAIL;TYPE=INTERNET:{}", escape(email)));
+    }
+    if let Some(company) = &contact.company {
+        lines.push(format!("ORG:{}", escape(company)));
+    }
+    if let Some(address) = &contact.address {
+        // Post office box and extended address come first and are unused.
+        let parts: Vec<String> = address
+            .parts()
+            .iter()
+            .map(|(_, part)| part.map(escape).unwrap_or_default())
+            .collect();
+        lines.push(format!("ADR:;;{}", parts.join(";")));
+    }
+    if let Some(avatar_url) = &contact.avatar_url {
+        lines.push(format!("PHOTO;VALUE=uri:{avatar_url}"));
+    }
+    if let Some(birthday) = contact.birthday {
+        lines.push(format!("BDAY:{}", birthday.format("%Y-%m-%d")));
+    }
+    if let Some(notes) = &contact.notes {
+        lines.push(format!("NOTE:{}", escape(notes)));
+    }
+    if !contact.tags.is_empty() {
+        let categories: Vec<String> = contact.tags.iter().map(|tag| escape(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    lines.push("END:VCARD".to_string());
+
+    let mut card = lines.join("\r\n");
+    card.push_str("\r\n");
+    card
+}
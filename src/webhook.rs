@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use reqwest::{Client, Url};
+
+use crate::events::{ContactEvent, Events, Received};
+
+const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Starts the background task that sends every published event to `url`,
+/// one at a time and in order.
+pub fn spawn(url: &str, events: &Events) -> anyhow::Result<()> {
+    let url = Url::parse(url)?;
+    let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+
+    let mut subscription = events.subscribe();
+    tokio::spawn(async move {
+        while let Some(received) = subscription.recv().await {
+            match received {
+                Received::Event(event) => deliver(&client, &url, &event).await,
+                Received::Lagged(skipped) => {
+                    tracing::error!("webhook delivery fell behind; dropped {skipped} events");
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// POSTs the event, retrying with exponential backoff on network errors and
+/// non-2xx responses. Failures are only logged.
+async fn deliver(client: &Client, url: &Url, event: &ContactEvent) {
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(url.clone())
+            .json(event)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+
+        match result {
+            Ok(_) => return,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                tracing::warn!(
+                    event = event.event,
+                    contact_id = %event.contact.id,
+                    attempt,
+                    "webhook delivery failed, retrying in {backoff:?}: {e}"
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => {
+                tracing::error!(
+                    event = event.event,
+                    contact_id = %event.contact.id,
+                    "webhook delivery failed after {MAX_ATTEMPTS} attempts: {e}"
+                );
+            }
+        }
+    }
+}
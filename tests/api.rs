@@ -0,0 +1,569 @@
+//! End-to-end tests of the HTTP API against a real Postgres.
+//!
+//! Each test starts a throwaway Postgres container, so they need Docker and
+//! are ignored by default:
+//!
+//! ```sh
+//! cargo test -- --ignored
+//! ```
+//!
+//! Set `TEST_DATABASE_URL` to run them against an existing database instead.
+
+use std::net::SocketAddr;
+
+use phone_book::Config;
+use reqwest::{header, Client, StatusCode};
+use serde_json::{json, Value};
+use testcontainers_modules::{
+    postgres::Postgres,
+    testcontainers::{runners::AsyncRunner, ContainerAsync},
+};
+
+/// Fictional number; the test deletes its contact again, which frees it.
+const PHONE: &str = "+12025550199";
+
+/// The app on an ephemeral port. The container is kept alive until the test
+/// drops the server.
+struct TestServer {
+    base_url: String,
+    client: Client,
+    _container: Option<ContainerAsync<Postgres>>,
+}
+
+impl TestServer {
+    async fn start() -> Self {
+        Self::start_with(|_| {}).await
+    }
+
+    /// Starts the server with settings adjusted by `configure`.
+    async fn start_with(configure: impl FnOnce(&mut Config)) -> Self {
+        let (database_url, container) = match std::env::var("TEST_DATABASE_URL") {
+            Ok(url) => (url, None),
+            Err(_) => {
+                let container = Postgres::default()
+                    .start()
+                    .await
+                    .expect("failed to start Postgres; is Docker running?");
+                let host = container.get_host().await.unwrap();
+                let port = container.get_host_port_ipv4(5432).await.unwrap();
+                let url = format!("postgres://postgres:postgres@{host}:{port}/postgres");
+                (url, Some(container))
+            }
+        };
+
+        let mut config = Config {
+            database_url,
+            rate_limit_per_minute: 0,
+            ..Config::default()
+        };
+        configure(&mut config);
+        let pool = phone_book::connect(&config)
+            .await
+            .expect("failed to set up the database");
+        let app = phone_book::app(config, pool).expect("failed to build the app");
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        Self {
+            base_url: format!("http://{addr}"),
+            client: Client::new(),
+            _container: container,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1{path}", self.base_url)
+    }
+}
+
+async fn json_body(response: reqwest::Response) -> Value {
+    response.json().await.expect("response body is not JSON")
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn contact_lifecycle() {
+    let server = TestServer::start().await;
+    let client = &server.client;
+
+    // Create: the number is normalized and `Location` points at the contact.
+    let response = client
+        .post(server.url("/contacts"))
+        .json(&json!({
+            "first_name": "Ada",
+            "last_name": "Lovelace",
+            "phone": "+1 202-555-0199",
+            "email": "ada@example.com"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let location = response.headers()[header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let created = json_body(response).await;
+    let id = created["id"].as_str().expect("id is a string").to_string();
+    assert_eq!(location, format!("/v1/contacts/{id}"));
+    assert_eq!(created["first_name"], "Ada");
+    assert_eq!(created["last_name"], "Lovelace");
+    assert_eq!(created["phone"], PHONE);
+    assert_eq!(created["email"], "ada@example.com");
+    assert_eq!(created["version"], 1);
+    assert_eq!(created["is_favorite"], false);
+    assert_eq!(created["tags"], json!([]));
+    assert_eq!(
+        created["phones"],
+        json!([{ "label": "mobile", "number": PHONE }])
+    );
+    assert!(created["created_at"].is_string());
+    assert!(created.get("deleted_at").is_none());
+
+    // Get: the same body, with the version as its ETag.
+    let response = client
+        .get(server.url(&format!("/contacts/{id}")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::ETAG], "\"1\"");
+    let body = response.bytes().await.unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), created);
+
+    // Head: the same status, ETag, and length, without the body.
+    let response = client
+        .head(server.url(&format!("/contacts/{id}")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::ETAG], "\"1\"");
+    assert_eq!(
+        response.headers()[header::CONTENT_LENGTH],
+        body.len().to_string()
+    );
+    assert!(response.bytes().await.unwrap().is_empty());
+
+    // XML has a tag of its own, so the JSON tag doesn't revalidate it.
+    for (if_none_match, expected) in [
+        ("\"1\"", StatusCode::OK),
+        ("\"1-xml\"", StatusCode::NOT_MODIFIED),
+    ] {
+        let response = client
+            .get(server.url(&format!("/contacts/{id}")))
+            .header(header::ACCEPT, "application/xml")
+            .header(header::IF_NONE_MATCH, if_none_match)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), expected);
+        assert_eq!(response.headers()[header::ETAG], "\"1-xml\"");
+        assert_eq!(response.headers()[header::VARY], "accept");
+    }
+
+    // So do a `fields` subset and another phone format.
+    let response = client
+        .get(server.url(&format!("/contacts/{id}")))
+        .query(&[("fields", "phone,id"), ("format", "national")])
+        .header(header::IF_NONE_MATCH, "\"1\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::ETAG],
+        "\"1-fields=id+phone-national\""
+    );
+
+    // vCard: `/vcard` serves the same file as the `.vcf` path.
+    let response = client
+        .get(server.url(&format!("/contacts/{id}/vcard")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/vcard"));
+    let card = response.text().await.unwrap();
+    assert!(card.starts_with("BEGIN:VCARD\r\n"));
+    assert!(card.contains(&format!("TEL;TYPE=CELL:{PHONE}")));
+    let response = client
+        .get(server.url(&format!("/contacts/{id}.vcf")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), card);
+
+    // List: a repeated page is served from the cache.
+    for expected in ["MISS", "HIT"] {
+        let response = client
+            .get(server.url("/contacts?limit=5"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-cache"], expected);
+    }
+
+    // Update: only the given fields change and the version is bumped.
+    let response = client
+        .patch(server.url(&format!("/contacts/{id}")))
+        .header(header::IF_MATCH, "\"1\"")
+        .json(&json!({ "email": "ada@analytical.engine", "tags": ["Math"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::ETAG], "\"2\"");
+    let updated = json_body(response).await;
+    assert_eq!(updated["email"], "ada@analytical.engine");
+    assert_eq!(updated["tags"], json!(["math"]));
+    assert_eq!(updated["first_name"], "Ada");
+    assert_eq!(updated["version"], 2);
+
+    // A stale ETag is refused.
+    let response = client
+        .patch(server.url(&format!("/contacts/{id}")))
+        .header(header::IF_MATCH, "\"1\"")
+        .json(&json!({ "first_name": "Augusta" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(json_body(response).await["code"], "precondition_failed");
+
+    // Search finds the contact by phone and by name.
+    let response = client
+        .get(server.url("/contacts/search"))
+        .query(&[("phone", PHONE)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let found = json_body(response).await;
+    assert_eq!(found.as_array().unwrap().len(), 1);
+    assert_eq!(found[0], updated);
+
+    let response = client
+        .get(server.url("/contacts/search"))
+        .query(&[("name", "lovelace")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let found = json_body(response).await;
+    assert!(found
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|contact| contact["id"] == id));
+
+    // Avatar: a non-ASCII URL is stored percent-encoded, so it can be sent
+    // back as the redirect's `Location`.
+    let response = client
+        .patch(server.url(&format!("/contacts/{id}")))
+        .json(&json!({ "avatar_url": " https://example.com/ädä.png " }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let avatar_url = "https://example.com/%C3%A4d%C3%A4.png";
+    assert_eq!(json_body(response).await["avatar_url"], avatar_url);
+
+    let no_redirects = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let response = no_redirects
+        .get(server.url(&format!("/contacts/{id}/avatar")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(response.headers()[header::LOCATION], avatar_url);
+
+    // Delete: the contact disappears from lookups and search.
+    let response = client
+        .delete(server.url(&format!("/contacts/{id}")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // Every write empties the list cache.
+    let response = client
+        .get(server.url("/contacts?limit=5"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-cache"], "MISS");
+
+    let response = client
+        .get(server.url(&format!("/contacts/{id}")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        json_body(response).await,
+        json!({ "error": "Contact not found", "code": "not_found" })
+    );
+
+    let response = client
+        .get(server.url("/contacts/search"))
+        .query(&[("phone", PHONE)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await, json!([]));
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn patch_distinguishes_null_from_absent() {
+    let server = TestServer::start().await;
+    let client = &server.client;
+
+    let response = client
+        .post(server.url("/contacts"))
+        .json(&json!({
+            "first_name": "Grace",
+            "last_name": "Hopper",
+            "phone": "+12025550197",
+            "email": "grace@example.com",
+            "birthday": "1906-12-09"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let id = json_body(response).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let url = server.url(&format!("/contacts/{id}"));
+
+    // Absent keys leave the fields alone.
+    let response = client
+        .patch(&url)
+        .json(&json!({ "notes": "COBOL" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let contact = json_body(response).await;
+    assert_eq!(contact["email"], "grace@example.com");
+    assert_eq!(contact["birthday"], "1906-12-09");
+    assert_eq!(contact["notes"], "COBOL");
+
+    // `null` clears them.
+    let response = client
+        .patch(&url)
+        .json(&json!({ "email": null }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let contact = json_body(response).await;
+    assert_eq!(contact["email"], Value::Null);
+    assert_eq!(contact["birthday"], "1906-12-09");
+
+    let response = client
+        .patch(&url)
+        .json(&json!({ "birthday": null }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let contact = json_body(response).await;
+    assert_eq!(contact["birthday"], Value::Null);
+    assert_eq!(contact["email"], Value::Null);
+    assert_eq!(contact["notes"], "COBOL");
+
+    // The cleared values are stored, not just echoed back.
+    let response = client.get(&url).send().await.unwrap();
+    let stored = json_body(response).await;
+    assert_eq!(stored["email"], Value::Null);
+    assert_eq!(stored["birthday"], Value::Null);
+
+    let response = client.delete(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn rejects_invalid_requests() {
+    let server = TestServer::start().await;
+    let client = &server.client;
+
+    // Every invalid field is reported at once, and nothing is stored.
+    let response = client
+        .post(server.url("/contacts"))
+        .json(&json!({
+            "first_name": " ",
+            "last_name": "Lovelace",
+            "phone": "+12025550198",
+            "email": "nope"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = json_body(response).await;
+    assert_eq!(body["code"], "validation_failed");
+    let errors = body["errors"].as_object().expect("errors is an object");
+    for field in ["first_name", "email"] {
+        assert!(errors.contains_key(field), "no error for `{field}`");
+    }
+    assert!(!errors.contains_key("last_name"));
+
+    // A backup gets the same checks; one bad contact restores nothing.
+    let backup = |id: uuid::Uuid, phone: &str, email: &str, country_code: &str| {
+        json!({
+            "id": id,
+            "first_name": "Charles",
+            "last_name": "Babbage",
+            "phone": phone,
+            "email": email,
+            "country_code": country_code,
+            "is_favorite": false,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "version": 1
+        })
+    };
+    let response = client
+        .post(server.url("/contacts/import.json"))
+        .json(&json!([
+            backup(
+                uuid::Uuid::new_v4(),
+                "+1 202-555-0188",
+                "charles@example.com",
+                "US"
+            ),
+            backup(uuid::Uuid::new_v4(), "+12025550189", "nope", "USA"),
+        ]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = json_body(response).await;
+    let errors = body["errors"].as_object().expect("errors is an object");
+    for field in ["contacts[1].email", "contacts[1].country_code"] {
+        assert!(errors.contains_key(field), "no error for `{field}`");
+    }
+    assert!(!errors.keys().any(|field| field.starts_with("contacts[0]")));
+    for phone in ["+12025550188", "+12025550189"] {
+        let response = client
+            .get(server.url("/contacts/search"))
+            .query(&[("phone", phone)])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(json_body(response).await, json!([]));
+    }
+
+    let response = client
+        .get(server.url("/contacts/search"))
+        .query(&[("phone", "+12025550198")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await, json!([]));
+
+    let response = client
+        .get(server.url("/contacts/not-a-uuid"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        json_body(response).await,
+        json!({ "error": "invalid contact id", "code": "invalid_uuid" })
+    );
+
+    let response = client
+        .delete(server.url("/contacts/not-a-uuid"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["code"], "invalid_uuid");
+
+    let response = client
+        .patch(server.url(&format!("/contacts/{}", uuid::Uuid::nil())))
+        .json(&json!({ "first_name": "Nobody" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .get(server.url("/contacts/search"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .put(server.url("/contacts"))
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(
+        response.headers()[header::ALLOW],
+        "GET,HEAD,POST,PATCH,DELETE"
+    );
+    assert_eq!(json_body(response).await["code"], "method_not_allowed");
+
+    let response = client.get(server.url("/contact")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        json_body(response).await,
+        json!({ "error": "route not found", "code": "not_found" })
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn rate_limit_ignores_spoofed_forwarded_headers() {
+    let server = TestServer::start_with(|config| config.rate_limit_per_minute = 2).await;
+    let client = &server.client;
+
+    // The test client isn't a trusted proxy, so every request counts against
+    // its own address whatever it claims to be forwarding for.
+    for i in 0..2 {
+        let response = client
+            .get(server.url("/contacts"))
+            .header("X-Forwarded-For", format!("203.0.113.{i}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = client
+        .get(server.url("/contacts"))
+        .header("X-Forwarded-For", "203.0.113.99")
+        .header("X-Real-IP", "198.51.100.7")
+        .header("Forwarded", "for=192.0.2.60")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+}